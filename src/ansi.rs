@@ -0,0 +1,106 @@
+//! ANSI escape sequences for writing rendered buffers as terminal output, shared by [`cast`](crate::cast) recordings and
+//! [`export::to_ansi()`](crate::export::to_ansi).
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier},
+};
+use std::fmt::Write;
+
+/// Resets all attributes set by previous SGR sequences.
+pub(crate) const RESET: &str = "\x1b[0m";
+
+/// Clears the whole screen and moves the cursor to the top left corner.
+pub(crate) const CLEAR: &str = "\x1b[2J\x1b[H";
+
+pub(crate) const HIDE_CURSOR: &str = "\x1b[?25l";
+
+const MODIFIERS: [(Modifier, u8); 9] = [
+    (Modifier::BOLD, 1),
+    (Modifier::DIM, 2),
+    (Modifier::ITALIC, 3),
+    (Modifier::UNDERLINED, 4),
+    (Modifier::SLOW_BLINK, 5),
+    (Modifier::RAPID_BLINK, 6),
+    (Modifier::REVERSED, 7),
+    (Modifier::HIDDEN, 8),
+    (Modifier::CROSSED_OUT, 9),
+];
+
+/// Returns SGR sequence resetting all attributes and setting the colors and modifiers of the cell.
+pub(crate) fn sgr(cell: &Cell) -> String {
+    let mut sequence = String::from("\x1b[0");
+    for (modifier, code) in MODIFIERS {
+        if cell.modifier.contains(modifier) {
+            let _ = write!(sequence, ";{code}");
+        }
+    }
+    write_color(&mut sequence, cell.fg, 30);
+    write_color(&mut sequence, cell.bg, 40);
+    sequence.push('m');
+    sequence
+}
+
+/// Writes parameters of the color, `base` is 30 for foreground and 40 for background.
+fn write_color(sequence: &mut String, color: Color, base: u8) {
+    let _ = match color {
+        Color::Reset => Ok(()),
+        Color::Black => write!(sequence, ";{base}"),
+        Color::Red => write!(sequence, ";{}", base + 1),
+        Color::Green => write!(sequence, ";{}", base + 2),
+        Color::Yellow => write!(sequence, ";{}", base + 3),
+        Color::Blue => write!(sequence, ";{}", base + 4),
+        Color::Magenta => write!(sequence, ";{}", base + 5),
+        Color::Cyan => write!(sequence, ";{}", base + 6),
+        Color::Gray => write!(sequence, ";{}", base + 7),
+        Color::DarkGray => write!(sequence, ";{}", base + 60),
+        Color::LightRed => write!(sequence, ";{}", base + 61),
+        Color::LightGreen => write!(sequence, ";{}", base + 62),
+        Color::LightYellow => write!(sequence, ";{}", base + 63),
+        Color::LightBlue => write!(sequence, ";{}", base + 64),
+        Color::LightMagenta => write!(sequence, ";{}", base + 65),
+        Color::LightCyan => write!(sequence, ";{}", base + 66),
+        Color::White => write!(sequence, ";{}", base + 67),
+        Color::Indexed(index) => write!(sequence, ";{};5;{index}", base + 8),
+        Color::Rgb(r, g, b) => write!(sequence, ";{};2;{r};{g};{b}", base + 8),
+    };
+}
+
+/// Returns terminal output turning the screen showing `previous` buffer into `next` buffer.
+///
+/// If there is no previous buffer or its size differs, the screen is cleared and the whole `next` buffer is written.
+/// Cursor positions are relative to the buffer area, so buffers of inline viewports are written from the top left corner.
+pub(crate) fn diff(previous: Option<&Buffer>, next: &Buffer) -> String {
+    let mut output = String::new();
+    let cleared;
+    let previous = match previous {
+        Some(previous) if previous.area == next.area => previous,
+        _ => {
+            output.push_str(RESET);
+            output.push_str(CLEAR);
+            cleared = Buffer::empty(next.area);
+            &cleared
+        }
+    };
+
+    let mut cursor = None;
+    let mut style = None;
+    for (x, y, cell) in previous.diff(next) {
+        if cursor != Some((x, y)) {
+            let (row, column) = (y - next.area.y + 1, x - next.area.x + 1);
+            let _ = write!(output, "\x1b[{row};{column}H");
+        }
+        let cell_style = sgr(cell);
+        if style.as_ref() != Some(&cell_style) {
+            output.push_str(&cell_style);
+            style = Some(cell_style);
+        }
+        output.push_str(cell.symbol());
+        cursor = Some((x + 1, y));
+    }
+
+    if style.is_some() {
+        output.push_str(RESET);
+    }
+    output
+}
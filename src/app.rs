@@ -1,146 +1,939 @@
-//! This module containts the primary methods for initializing, starting and configuring application.
-//! 
+//! This module contains the primary methods for initializing, starting and configuring application.
+//!
+//! Application is represented by an owned [`App`] handle, which is built with the initial view and started
+//! with [`App::run()`]. While running, views receive a cloneable [`AppContext`] in [`View::handle_events()`],
+//! which allows to quit, navigate and access shared state.
+//!
+//! ```no_run
+//! # use ratatui::{buffer::Buffer, layout::Rect};
+//! # use ratatuio::{app::App, view::View};
+//! # struct MainPage;
+//! # impl View for MainPage {
+//! #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+//! # }
+//! App::new(MainPage).run()?;
+//! # Ok::<(), ratatuio::Error>(())
+//! ```
+//!
 //! # Methods
-//! - [`init()`] - Iinitializes application.
+//! Free functions below are a thin layer over a default [`App`] instance created by [`init()`].
+//! - [`init()`] - Initializes application.
 //! - [`run()`] - Starts running loop.
+//! - [`run_with_result()`] - Starts running loop and returns value provided to [`AppContext::quit_with()`].
+//! - [`quit()`] - Exits out of the running loop.
+//! - [`run_async()`] - Starts running loop asynchronously, requires `tokio` feature.
+//! - [`sender()`] - Returns [`Sender`] posting messages into the running loop from any thread.
+//! - [`context()`] - Returns [`AppContext`] of the application.
 //! - [`change_view()`] - Changes main view.
+//! - [`push_view()`] - Suspends current view and shows a new one on top of it.
+//! - [`pop_view()`] - Returns to the previously suspended view.
+//! - [`replace_view()`] - Replaces current view without touching suspended views.
+//! - [`pop_to_root()`] - Returns to the first view on the navigation stack.
+//! - [`navigate()`] - Shows a view registered in [`router`](crate::router) under matching path.
+//!
+//! # Navigation stack
+//! Views pushed with [`push_view()`] are placed on top of a navigation stack. The view that was
+//! active before is not dropped but suspended, so after [`pop_view()`] it is resumed with all of its
+//! state (scroll position, selection, etc.) intact. Views are notified about these changes by lifecycle methods
+//! like [`View::on_enter()`] and [`View::on_suspend()`].
+//!
+//! All navigation requests are queued and applied in order at the beginning of the next application
+//! running loop, so they can be safely called from inside [`View::handle_events()`].
+//!
+//! # Overlays
+//! Dialogs and popups can be shown above the active view with [`AppContext::open_overlay()`]. The view below stays
+//! visible, while the top [`Overlay`] receives input until it is closed, optionally returning a value to its opener.
+//!
+//! # Notifications
+//! Short messages, ex. that a file was saved or a request failed, can be shown from any view or thread with
+//! [`notify()`] or [`AppContext::notify()`]. They are drawn as stacked toasts above everything else and hidden
+//! after a timeout, even if no input arrives.
+//!
+//! # Messages
+//! Background threads can deliver results to the current view by posting [`Message`]s with a [`Sender`].
+//! Each message wakes the running loop and is passed to [`View::handle_message()`].
+//!
+//! # Middleware
+//! Global key bindings, like quitting with `Ctrl+C`, do not have to be handled by every view. [`Middleware`]s added
+//! with [`App::middleware()`] see each event before the active view and can consume it, transform it or pass it through.
+//! Keys can also be bound to named actions with a [`Keymap`](crate::keymap::Keymap) set by [`App::keymap()`].
+//!
+//! # Async
+//! With `tokio` feature enabled the loop can be driven asynchronously with [`App::run_async()`], which reads terminal
+//! events with crossterm's `EventStream` and awaits `View::handle_events_async()`. Views can then start futures with
+//! `AppContext::spawn()`, whose results are delivered back as [`Message`]s.
 
-use crate::view::{View, ViewWidgetWrapper};
-use crossterm::event::{self};
-use ratatui::widgets::WidgetRef;
-use std::{io, sync::RwLock};
+mod context;
+mod crash;
+mod help;
+pub(crate) mod inbox;
+mod message;
+mod middleware;
+mod overlay;
+pub(crate) mod runtime;
+#[cfg(feature = "tokio")]
+mod runtime_async;
+mod toast;
 
-/// A global, thread-safe, mutable application state.
-///
-/// This static variable holds the application state. It is wrapped in an [`RwLock`] to allow
-/// safe concurrent read/write access. The `Option<App>` inside the `RwLock` allows the state
-/// to be either `Some(App)` when the application is initialized or `None` if the application
-/// has not been initialized yet.
-///
-/// # Usage
-/// 
-/// Before interacting with the application, you must call [`init()`] to initialize it. After that,
-/// the state can be accessed or modified safely using this global variable.
-///
-/// This variable is used to track the running status of the application and is shared across the
-/// application runtime.
-pub static APPLICATION: RwLock<Option<App>> = RwLock::new(None);
+pub use context::AppContext;
+pub use crash::CrashReport;
+pub use message::{Message, Sender};
+pub use middleware::{DebugMode, DebugToggle, Flow, Middleware, QuitOnCtrlC};
+pub use overlay::Overlay;
+pub use toast::{Corner, Level};
 
-/// A global, thread-safe, mutable view state.
-///
-/// This static variable holds the current view of the application. It is wrapped in an [`RwLock`] 
-/// to ensure safe concurrent access and modification. The `Option<Box<dyn View + Sync + Send>>` 
-/// allows the application to store the current view as a dynamic trait object that implements the 
-/// [`View`] trait.
-///
-/// # Usage
-/// 
-/// This variable is used to store the current view of the application, which is rendered to the
-/// terminal. The [`init()`] function sets the initial view, and later the view can be changed using
-/// the [`change_view()`] function.
-///
-/// Since it is wrapped in an `RwLock`, it allows for multiple readers, but only one writer at a time.
-/// Accessing or modifying the view requires acquiring the lock.
-pub static VIEW: RwLock<Option<Box<dyn View + Sync + Send>>> = RwLock::new(None);
+use crate::{
+    backend::{TerminalBackend, TerminalConfig},
+    cast::CastRecorder,
+    keymap::{KeyMatcher, Keymap},
+    recording::{Recorder, Recording},
+    router::Router,
+    view::{AnyView, BoxedView, View},
+    Error,
+};
+use context::Navigation;
+use crash::CrashHandler;
+use crossterm::event::{Event, KeyEvent, KeyboardEnhancementFlags};
+use help::Help;
+use ratatui::{backend::CrosstermBackend, Viewport};
+use runtime::BackendLoop;
+use std::{
+    any::Any,
+    fs::File,
+    io::{self, BufWriter},
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+use toast::Toasts;
 
-static mut _CHANGE_VIEW: bool = false;
-static mut _NEXT_VIEW: Option<Box<dyn View + Sync + Send>> = None;
+/// Default application created by [`init()`] and taken out by [`run()`].
+static DEFAULT_APP: Mutex<Option<App>> = Mutex::new(None);
 
+/// Context of the default application, used by free functions of this module.
+static DEFAULT_CONTEXT: RwLock<Option<AppContext>> = RwLock::new(None);
+
+/// An owned application handle.
+///
+/// Holds the navigation stack of views and the [`AppContext`] shared with them. Multiple applications can exist
+/// in one process, each with its own views and state.
 pub struct App {
-    pub is_running: bool,
+    pub(crate) context: AppContext,
+    /// Views from the root view at the bottom to the active view at the top.
+    pub(crate) views: Vec<BoxedView>,
+    /// Modes set with [`AppContext::set_mode()`], one for every view in `views`.
+    modes: Vec<Option<String>>,
+    /// Overlays drawn above the active view, the top one receives input.
+    pub(crate) overlays: Vec<Overlay>,
+    /// Type names of views in order they became active, recorded only if set to `Some`.
+    pub(crate) history: Option<Vec<&'static str>>,
+    tick_rate: Option<Duration>,
+    pub(crate) frame_interval: Duration,
+    crash_handler: Option<CrashHandler>,
+    record_path: Option<PathBuf>,
+    /// Writes events to [`App::record_to()`] file while the loop is running.
+    pub(crate) recorder: Option<Recorder>,
+    pub(crate) replay: Option<Recording>,
+    cast_path: Option<PathBuf>,
+    cast: Option<CastRecorder<BufWriter<File>>>,
+    screenshot_key: Option<(KeyEvent, PathBuf)>,
+    /// Set when the screenshot key was pressed, the screen is saved after the next frame is drawn.
+    screenshot_requested: bool,
+    backend: Option<Box<dyn BackendLoop>>,
+    pub(crate) viewport: Viewport,
+    terminal: TerminalConfig,
+    middlewares: Vec<Box<dyn Middleware>>,
+    keymap: Keymap,
+    /// Keys of an unfinished sequence of the keymap.
+    pub(crate) key_matcher: KeyMatcher,
+    help_key: Option<KeyEvent>,
+    /// State of the help overlay while it is open.
+    help: Option<Help>,
+    /// Toasts shown with [`AppContext::notify()`].
+    pub(crate) toasts: Toasts,
 }
 
-/// Initializes the application with the provided view. Must be run before any other application code like [`run()`].
-/// 
-/// This function:
-/// - Initializes [`VIEW`]
-/// - Initializes [`APPLICATION`]
-/// 
-/// # Parameters:
-/// - `view`: A struct implementing the [`View`] trait. Represents the initial view of the application.
-pub fn init<T: View + Sync + Send + 'static>(view: T) {
-    let mut mainpage = VIEW.write().expect("create custom error here");
-    if mainpage.is_none() {
-        *mainpage = Some(Box::new(view));
+impl App {
+    /// Creates new application with the provided view.
+    ///
+    /// # Parameters:
+    /// - `view`: A struct implementing the [`View`] trait. Represents the initial view of the application.
+    pub fn new<T: View + Sync + Send + 'static>(view: T) -> Self {
+        App {
+            context: AppContext::new(),
+            views: vec![Box::new(view)],
+            modes: vec![None],
+            overlays: Vec::new(),
+            history: None,
+            tick_rate: None,
+            frame_interval: Duration::from_secs(1) / 60,
+            crash_handler: None,
+            record_path: None,
+            recorder: None,
+            replay: None,
+            cast_path: None,
+            cast: None,
+            screenshot_key: None,
+            screenshot_requested: false,
+            backend: None,
+            viewport: Viewport::Fullscreen,
+            terminal: TerminalConfig::default(),
+            middlewares: Vec::new(),
+            keymap: Keymap::new(),
+            key_matcher: KeyMatcher::default(),
+            help_key: None,
+            help: None,
+            toasts: Toasts::default(),
+        }
     }
 
-    let mut app = APPLICATION.write().expect("create custom error here");
-    if app.is_none() {
-        *app = Some(App { is_running: true });
+    /// Sets how often [`View::on_tick()`] of the current view is called.
+    ///
+    /// By default views do not receive ticks and are redrawn only after an event, so views showing live data
+    /// like clocks or metrics should set a tick rate.
+    ///
+    /// # Parameters:
+    /// - `tick_rate`: Time between two consecutive ticks. With `Duration::MAX` views are never ticked.
+    pub fn tick_rate(mut self, tick_rate: Duration) -> Self {
+        self.tick_rate = Some(tick_rate);
+        self
+    }
+
+    /// Sets maximum number of frames drawn per second. Defaults to 60.
+    ///
+    /// View is redrawn only after an event, tick or navigation, but never more often than the frame rate allows.
+    ///
+    /// # Parameters:
+    /// - `frame_rate`: Target number of frames per second. Rates which are not positive, or so low that the
+    ///   interval between frames cannot be represented, remove the limit.
+    pub fn frame_rate(mut self, frame_rate: f64) -> Self {
+        self.frame_interval =
+            Duration::try_from_secs_f64(frame_rate.recip()).unwrap_or(Duration::ZERO);
+        self
+    }
+
+    /// Sets handler called when a view or a task spawned with [`AppContext::spawn()`] panics, or the application loop exits
+    /// with an error, ex. to log crash details to a file.
+    ///
+    /// Handler is called after the terminal was restored. Panic message is printed by the previously installed panic hook
+    /// and the error is returned from [`App::run()`] as usual.
+    ///
+    /// ```no_run
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::App, view::View};
+    /// # struct MainPage;
+    /// # impl View for MainPage {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    /// # }
+    /// App::new(MainPage)
+    ///     .on_crash(|report| {
+    ///         let _ = std::fs::write("crash.log", report.to_string());
+    ///     })
+    ///     .run()?;
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    pub fn on_crash(mut self, handler: impl Fn(&CrashReport) + Send + Sync + 'static) -> Self {
+        self.crash_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Adds middleware which sees every terminal event before the active view and can consume or transform it.
+    /// Middlewares are called in order they were added. See [`Middleware`].
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+
+    /// Sets keymap resolving pressed keys to actions passed to [`View::handle_action()`], after the event passed
+    /// through middlewares. See [`keymap`](crate::keymap).
+    pub fn keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Sets routes resolved by [`AppContext::navigate()`]. See [`Router`].
+    pub fn router(self, router: Router) -> Self {
+        self.context.set_router(Some(router));
+        self
+    }
+
+    /// Sets key which opens and closes help overlay listing bindings of the [`Keymap`] set with [`App::keymap()`],
+    /// which apply to the active view in its current mode, grouped by context. Actions are described by
+    /// [`Keymap::describe()`](crate::keymap::Keymap::describe) or by their names.
+    ///
+    /// Overlay is drawn on top of the view and receives all key presses while it is open: typed text filters the
+    /// bindings, arrows scroll them and `Esc` closes it.
+    ///
+    /// ```
+    /// # use crossterm::event::KeyCode;
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::App, keymap::Keymap, testing::Harness, view::View};
+    /// # struct MainPage;
+    /// # impl View for MainPage {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    /// # }
+    /// let keymap = Keymap::new()
+    ///     .bind(Keymap::GLOBAL, "app.quit", KeyCode::Char('q'))
+    ///     .bind(Keymap::GLOBAL, "app.save", KeyCode::Char('s'))
+    ///     .describe("app.quit", "Quit");
+    /// let app = App::new(MainPage).keymap(keymap).help_key(KeyCode::Char('?'));
+    /// let mut harness = Harness::new(app, 30, 8)?;
+    ///
+    /// harness.key(KeyCode::Char('?')).text("qu").run()?;
+    /// assert!(harness.lines().iter().any(|line| line.contains("q  Quit")));
+    /// assert!(!harness.lines().iter().any(|line| line.contains("app.save")));
+    /// assert!(harness.is_running());
+    ///
+    /// harness.key(KeyCode::Esc).run()?;
+    /// assert!(harness.lines().iter().all(|line| line.trim().is_empty()));
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    ///
+    /// # Parameters:
+    /// - `key`: Key code, or key event with modifiers, ex. `KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL)`.
+    pub fn help_key(mut self, key: impl Into<KeyEvent>) -> Self {
+        self.help_key = Some(key.into());
+        self
+    }
+
+    /// Sets corner of the terminal where toasts shown with [`AppContext::notify()`] are stacked. Defaults to
+    /// [`Corner::TopRight`].
+    pub fn toast_corner(mut self, corner: Corner) -> Self {
+        self.toasts.corner = corner;
+        self
+    }
+
+    /// Sets how long toasts shown with [`AppContext::notify()`] stay visible. Defaults to 5 seconds.
+    ///
+    /// The loop wakes up to hide them, so tick rate does not have to be set. With `Duration::MAX` toasts are shown
+    /// until they are dismissed with [`App::dismiss_toast_key()`].
+    ///
+    /// ```
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::App, view::View};
+    /// # use std::time::Duration;
+    /// # struct MainPage;
+    /// # impl View for MainPage {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    /// # }
+    /// let app = App::new(MainPage).toast_timeout(Duration::from_secs(2));
+    /// ```
+    pub fn toast_timeout(mut self, timeout: Duration) -> Self {
+        self.toasts.timeout = timeout;
+        self
+    }
+
+    /// Sets key which hides the newest toast shown with [`AppContext::notify()`] before its timeout. The key is
+    /// delivered to the view as usual while no toast is visible.
+    ///
+    /// # Parameters:
+    /// - `key`: Key code, or key event with modifiers, ex. `KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL)`.
+    pub fn dismiss_toast_key(mut self, key: impl Into<KeyEvent>) -> Self {
+        self.toasts.dismiss_key = Some(key.into());
+        self
+    }
+
+    /// Sets ratatui backend the application draws on. Defaults to crossterm on stdout.
+    ///
+    /// Terminal is prepared and restored by the backend, see [`backend`](crate::backend) for provided implementations.
+    ///
+    /// # Parameters:
+    /// - `backend`: Backend implementing [`TerminalBackend`], ex. `CrosstermBackend::new(io::stderr())`.
+    pub fn with_backend<B: TerminalBackend + Send + 'static>(mut self, backend: B) -> Self {
+        self.backend = Some(Box::new(backend));
+        self
+    }
+
+    /// Sets the area of the terminal the application draws on. Defaults to [`Viewport::Fullscreen`] on the alternate screen.
+    ///
+    /// With [`Viewport::Inline`] or [`Viewport::Fixed`] the alternate screen is not used, so small prompts and progress
+    /// views are drawn below the shell prompt, and their last frame stays in the scrollback after the application exits.
+    /// Views can print lines above the inline viewport with [`AppContext::insert_before()`].
+    ///
+    /// ```no_run
+    /// # use ratatui::{buffer::Buffer, layout::Rect, Viewport};
+    /// # use ratatuio::{app::App, view::View};
+    /// # struct Progress;
+    /// # impl View for Progress {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    /// # }
+    /// App::new(Progress).viewport(Viewport::Inline(3)).run()?;
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    pub fn viewport(mut self, viewport: Viewport) -> Self {
+        self.terminal.alternate_screen = viewport == Viewport::Fullscreen;
+        self.viewport = viewport;
+        self
+    }
+
+    /// Enables reporting of mouse clicks, drags, moves and scrolls as [`Event::Mouse`](crossterm::event::Event::Mouse).
+    ///
+    /// While mouse is captured, text in the terminal cannot be selected without holding a modifier key, usually Shift.
+    ///
+    /// ```no_run
+    /// # use crossterm::event::KeyboardEnhancementFlags;
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::App, view::View};
+    /// # struct MainPage;
+    /// # impl View for MainPage {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    /// # }
+    /// App::new(MainPage)
+    ///     .mouse_capture(true)
+    ///     .bracketed_paste(true)
+    ///     .focus_change(true)
+    ///     .keyboard_enhancement(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+    ///     .run()?;
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    pub fn mouse_capture(mut self, enabled: bool) -> Self {
+        self.terminal.mouse_capture = enabled;
+        self
+    }
+
+    /// Enables reporting of text pasted into the terminal as a single [`Event::Paste`](crossterm::event::Event::Paste)
+    /// instead of key presses of every character.
+    pub fn bracketed_paste(mut self, enabled: bool) -> Self {
+        self.terminal.bracketed_paste = enabled;
+        self
+    }
+
+    /// Enables reporting of [`Event::FocusGained`](crossterm::event::Event::FocusGained) and
+    /// [`Event::FocusLost`](crossterm::event::Event::FocusLost) when the terminal window gains or loses focus.
+    pub fn focus_change(mut self, enabled: bool) -> Self {
+        self.terminal.focus_change = enabled;
+        self
+    }
+
+    /// Enables enhanced keyboard reporting of terminals supporting the kitty keyboard protocol, ex. key releases or
+    /// modifiers of keys like `Ctrl+Enter`. Flags are not requested from terminals which do not support the protocol,
+    /// ex. on Windows, so the application runs with the usual key events there.
+    ///
+    /// # Parameters:
+    /// - `flags`: Requested enhancements, see [`KeyboardEnhancementFlags`](crossterm::event::KeyboardEnhancementFlags).
+    pub fn keyboard_enhancement(mut self, flags: KeyboardEnhancementFlags) -> Self {
+        self.terminal.keyboard_enhancement = Some(flags);
+        self
+    }
+
+    /// Records every terminal event read by the running application, with its time and the initial terminal size,
+    /// into the file, ex. to attach it to a bug report. File is created when the loop starts, also by
+    /// [`Harness`](crate::testing::Harness), and written after every event, so it is complete also after a crash.
+    ///
+    /// Recording can be loaded with [`Recording::load()`] and fed back with [`App::replay()`] or
+    /// [`Harness::replay()`](crate::testing::Harness::replay).
+    ///
+    /// ```no_run
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::App, recording::Recording, view::View};
+    /// # struct MainPage;
+    /// # impl View for MainPage {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    /// # }
+    /// App::new(MainPage).record_to("session.rec").run()?;
+    ///
+    /// // Later, reproduce the session.
+    /// App::new(MainPage).replay(Recording::load("session.rec")?).run()?;
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    ///
+    /// # Parameters:
+    /// - `path`: Path of the file, which is replaced if it exists.
+    pub fn record_to(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_path = Some(path.into());
+        self
+    }
+
+    /// Feeds events of the recording into the running loop at the times they were recorded, before any event is read
+    /// from the terminal. After the whole recording was replayed, the application continues with terminal input.
+    ///
+    /// Recorded [`Event::Resize`](crossterm::event::Event::Resize) events are delivered to the view, but the real
+    /// terminal keeps its size. To replay deterministically against a terminal of the recorded size, pass the application
+    /// to [`Harness`](crate::testing::Harness) or use [`Harness::replay()`](crate::testing::Harness::replay).
+    pub fn replay(mut self, recording: Recording) -> Self {
+        self.replay = Some(recording);
+        self
+    }
+
+    /// Records every drawn frame into an asciinema v2 `.cast` file, ex. to produce demos. See [`cast`](crate::cast).
+    ///
+    /// File is created when the first frame is drawn, also by [`Harness`](crate::testing::Harness), so casts can be
+    /// generated without a real terminal.
+    ///
+    /// # Parameters:
+    /// - `path`: Path of the file, which is replaced if it exists.
+    pub fn cast_to(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cast_path = Some(path.into());
+        self
+    }
+
+    /// Sets key which saves the current screen into the file, ex. to attach a screenshot to a bug report.
+    ///
+    /// Format is chosen by the file extension, see [`export::save()`](crate::export::save). Pressing the key again
+    /// replaces the file. The key is not delivered to the view.
+    ///
+    /// ```no_run
+    /// # use crossterm::event::KeyCode;
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::App, view::View};
+    /// # struct MainPage;
+    /// # impl View for MainPage {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    /// # }
+    /// App::new(MainPage)
+    ///     .screenshot_key(KeyCode::F(12), "screenshot.svg")
+    ///     .run()?;
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    ///
+    /// # Parameters:
+    /// - `key`: Key code, or key event with modifiers, ex. `KeyEvent::new(KeyCode::Char('s'), KeyModifiers::ALT)`.
+    /// - `path`: Path of the file, ex. `screenshot.html`.
+    pub fn screenshot_key(mut self, key: impl Into<KeyEvent>, path: impl Into<PathBuf>) -> Self {
+        self.screenshot_key = Some((key.into(), path.into()));
+        self
+    }
+
+    /// Sets initial application state of type `S`, which views can access with [`AppContext::with_state()`].
+    pub fn with_state<S: Any + Send>(self, state: S) -> Self {
+        self.context.set_state(state);
+        self
+    }
+
+    /// Returns [`AppContext`] of this application.
+    pub fn context(&self) -> AppContext {
+        self.context.clone()
+    }
+
+    /// Start running the application loop.
+    ///
+    /// As long as the application is running this function will:
+    /// - Refresh view and draw on terminal based on [`View::render_view()`], at most at [`App::frame_rate()`]
+    /// - Send current events to optional method [`View::handle_events()`]
+    /// - Call optional method [`View::on_tick()`] at [`App::tick_rate()`], if it was set
+    ///
+    /// Terminal is always restored when the loop exits, also when a view returns an error or panics.
+    ///
+    /// # Returns:
+    /// - `Ok(())` if application exits.
+    /// - [`Error::Io`] if any error occurs while drawing or while handling the events.
+    pub fn run(self) -> Result<(), Error> {
+        self.run_loop().map(|_| ())
+    }
+
+    /// Start running the application loop like [`App::run()`] and return the value provided to [`AppContext::quit_with()`].
+    ///
+    /// ```no_run
+    /// # use crossterm::event::{Event, KeyCode, KeyEventKind};
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::{App, AppContext}, view::View};
+    /// # use std::io;
+    /// struct Picker {
+    ///     items: Vec<String>,
+    ///     selected: usize,
+    /// }
+    ///
+    /// impl View for Picker {
+    /// #   fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    ///     fn handle_events(&mut self, event: &Event, ctx: &AppContext) -> io::Result<()> {
+    ///         if let Event::Key(key) = event {
+    ///             match key.code {
+    ///                 KeyCode::Enter => ctx.quit_with(self.items[self.selected].clone()),
+    ///                 KeyCode::Esc => ctx.quit(),
+    ///                 _ => {}
+    ///             }
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let picker = Picker { items: vec!["a".into(), "b".into()], selected: 0 };
+    /// let chosen: Option<String> = App::new(picker).run_with_result()?;
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    ///
+    /// # Returns:
+    /// - `Ok(Some(value))` if application exits with [`AppContext::quit_with()`].
+    /// - `Ok(None)` if application exits with [`AppContext::quit()`].
+    /// - [`Error::InvalidExitValue`] if provided value is not of type `T`.
+    /// - [`Error::Io`] if any error occurs while drawing or while handling the events.
+    pub fn run_with_result<T: Any>(self) -> Result<Option<T>, Error> {
+        downcast_exit_value(self.run_loop()?)
+    }
+
+    /// Start running the application loop asynchronously on the current tokio task. See [`App::run()`].
+    ///
+    /// Terminal events are read with crossterm's `EventStream` and passed to
+    /// [`View::handle_events_async()`](crate::view::View::handle_events_async). Tokio runtime has to have time driver enabled.
+    ///
+    /// ```no_run
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::App, view::View};
+    /// # use std::io;
+    /// # struct MainPage;
+    /// # impl View for MainPage {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    /// # }
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), ratatuio::Error> {
+    ///     App::new(MainPage).run_async().await
+    /// }
+    /// ```
+    ///
+    /// # Returns:
+    /// - `Ok(())` if application exits.
+    /// - [`Error::Io`] if any error occurs while drawing or while handling the events.
+    #[cfg(feature = "tokio")]
+    pub async fn run_async(self) -> Result<(), Error> {
+        self.run_loop_async().await.map(|_| ())
+    }
+
+    /// Start running the application loop asynchronously like [`App::run_async()`] and return the value provided to
+    /// [`AppContext::quit_with()`]. See [`App::run_with_result()`].
+    #[cfg(feature = "tokio")]
+    pub async fn run_async_with_result<T: Any>(self) -> Result<Option<T>, Error> {
+        downcast_exit_value(self.run_loop_async().await?)
+    }
+
+    /// Returns terminal features requested by the builder methods.
+    pub(crate) fn terminal_config(&self) -> TerminalConfig {
+        self.terminal.clone()
+    }
+
+    /// Takes backend set with [`App::with_backend()`], or creates the default one.
+    fn take_backend(&mut self) -> Box<dyn BackendLoop> {
+        self.backend
+            .take()
+            .unwrap_or_else(|| Box::new(CrosstermBackend::new(io::stdout())))
+    }
+
+    pub(crate) fn view(&self) -> Result<&(dyn AnyView + Sync + Send), Error> {
+        Ok(self.views.last().ok_or(Error::ViewMissing)?.as_ref())
+    }
+
+    pub(crate) fn view_mut(&mut self) -> Result<&mut (dyn AnyView + Sync + Send), Error> {
+        Ok(self.views.last_mut().ok_or(Error::ViewMissing)?.as_mut())
+    }
+
+    /// Returns the top overlay, or the active view if no overlay is open. It receives input and actions.
+    pub(crate) fn focused_mut(&mut self) -> Result<&mut (dyn AnyView + Sync + Send), Error> {
+        if self.overlays.is_empty() {
+            return self.view_mut();
+        }
+        Ok(self
+            .overlays
+            .last_mut()
+            .ok_or(Error::ViewMissing)?
+            .view
+            .as_mut())
+    }
+
+    /// Applies queued navigation requests and calls lifecycle methods of affected views. Returns `true` if there were any.
+    fn apply_navigation(&mut self) -> Result<bool, Error> {
+        let pending = self.context.take_navigation();
+        let navigated = !pending.is_empty();
+        let context = self.context.clone();
+
+        for navigation in pending {
+            match navigation {
+                Navigation::Push(next) => {
+                    self.close_overlays()?;
+                    self.view_mut()?.on_suspend(&context)?;
+                    self.push_view(next);
+                    self.view_mut()?.on_enter(&context)?;
+                    self.record_activation();
+                }
+                Navigation::Pop => {
+                    if self.views.len() > 1 {
+                        self.close_overlays()?;
+                        self.exit_view()?;
+                        self.view_mut()?.on_resume(&context)?;
+                        self.record_activation();
+                    }
+                }
+                Navigation::Replace(next) => {
+                    self.close_overlays()?;
+                    self.exit_view()?;
+                    self.push_view(next);
+                    self.view_mut()?.on_enter(&context)?;
+                    self.record_activation();
+                }
+                Navigation::PopToRoot => {
+                    if self.views.len() > 1 {
+                        self.close_overlays()?;
+                        while self.views.len() > 1 {
+                            self.exit_view()?;
+                        }
+                        self.view_mut()?.on_resume(&context)?;
+                        self.record_activation();
+                    }
+                }
+                Navigation::SetMode(mode) => {
+                    let active = self.modes.last().ok_or(Error::ViewMissing)?;
+                    if active.as_deref() != Some(mode.as_str()) {
+                        // Pending keys were typed for bindings of the previous mode, so they are resolved in it.
+                        let scopes = self.keymap_scopes()?;
+                        let matched = self.key_matcher.flush(&self.keymap, &scopes);
+                        context.set_pending_keys(None);
+                        for action in matched.actions {
+                            self.focused_mut()?.handle_action(&action, &context)?;
+                        }
+                        for key in matched.unmatched {
+                            self.focused_mut()?
+                                .handle_events(&Event::Key(key), &context)?;
+                        }
+
+                        let previous = self
+                            .modes
+                            .last_mut()
+                            .ok_or(Error::ViewMissing)?
+                            .replace(mode.clone());
+                        self.context.set_active_mode(Some(mode.clone()));
+                        self.view_mut()?
+                            .on_mode_change(previous.as_deref(), &mode, &context)?;
+                    }
+                }
+                Navigation::OpenOverlay(mut overlay) => {
+                    overlay.view.on_enter(&context)?;
+                    self.overlays.push(overlay);
+                }
+                Navigation::CloseOverlay(result) => {
+                    if let Some(mut overlay) = self.overlays.pop() {
+                        overlay.view.on_exit(&context)?;
+                        // Result is delivered straight to the opener, unlike messages posted with a sender.
+                        if let Some(result) = result {
+                            self.focused_mut()?.handle_message(result, &context)?;
+                        }
+                    }
+                }
+            }
+            self.context
+                .set_active_mode(self.modes.last().cloned().flatten());
+        }
+
+        Ok(navigated)
+    }
+
+    /// Calls [`View::on_enter()`] of the initial view.
+    pub(crate) fn enter(&mut self) -> Result<(), Error> {
+        let context = self.context.clone();
+        self.view_mut()?.on_enter(&context)?;
+        self.record_activation();
+        Ok(())
+    }
+
+    /// Calls [`View::on_exit()`] of all overlays and views, from the top one to the root.
+    pub(crate) fn exit(&mut self) -> Result<(), Error> {
+        self.close_overlays()?;
+        while !self.views.is_empty() {
+            self.exit_view()?;
+        }
+        Ok(())
+    }
+
+    fn record_activation(&mut self) {
+        if let (Some(history), Some(view)) = (&mut self.history, self.views.last()) {
+            history.push(view.type_name());
+        }
+    }
+
+    /// Closes all overlays without results and calls their [`View::on_exit()`], from the top one. Overlays are always
+    /// opened by the active view, so they are closed before it is suspended or removed.
+    fn close_overlays(&mut self) -> Result<(), Error> {
+        while let Some(mut overlay) = self.overlays.pop() {
+            overlay.view.on_exit(&self.context)?;
+        }
+        Ok(())
+    }
+
+    /// Places the view on top of the navigation stack without a mode.
+    fn push_view(&mut self, view: BoxedView) {
+        self.views.push(view);
+        self.modes.push(None);
+    }
+
+    /// Removes the active view from the navigation stack and calls its [`View::on_exit()`].
+    fn exit_view(&mut self) -> Result<(), Error> {
+        self.modes.pop();
+        let mut view = self.views.pop().ok_or(Error::ViewMissing)?;
+        view.on_exit(&self.context)?;
+        Ok(())
+    }
+}
+
+pub(crate) fn downcast_exit_value<T: Any>(
+    value: Option<Box<dyn Any + Send>>,
+) -> Result<Option<T>, Error> {
+    match value {
+        Some(value) => value
+            .downcast::<T>()
+            .map(|value| Some(*value))
+            .map_err(|_| Error::InvalidExitValue(std::any::type_name::<T>())),
+        None => Ok(None),
     }
 }
 
+/// Takes the default application out, so it can be run.
+fn take_default_app() -> Result<App, Error> {
+    DEFAULT_APP.lock()?.take().ok_or(Error::NotInitialized)
+}
 
-/// Start running the application loop. 
-/// 
-/// As long as the application is running this function will:
-/// - Refresh view and draw on terminal based on [`View::render_view()`]
-/// - Send current events to optional method [`View::handle_events()`]
-/// 
+/// Initializes the default application with the provided view. Must be run before any other free function of this
+/// module like [`run()`].
+///
+/// Does nothing if default application is already initialized and was not run yet.
+///
+/// # Parameters:
+/// - `view`: A struct implementing the [`View`] trait. Represents the initial view of the application.
+///
+/// # Returns:
+/// - `Ok(())` if application is initialized.
+/// - [`Error::LockPoisoned`] if default application could not be accessed.
+pub fn init<T: View + Sync + Send + 'static>(view: T) -> Result<(), Error> {
+    let mut app = DEFAULT_APP.lock()?;
+    if app.is_none() {
+        let new_app = App::new(view);
+        new_app.context.set_router(None);
+        *DEFAULT_CONTEXT.write()? = Some(new_app.context());
+        *app = Some(new_app);
+    }
+
+    Ok(())
+}
+
+/// Start running the default application loop. See [`App::run()`].
+///
 /// NOTE: This function MUST be run after [`init()`].
-/// 
+///
 /// # Returns:
 /// - `Ok(())` if application exits.
-/// - An `io::Error` if any error occurs while locking the shared resources or while handling the events.
-pub fn run() -> io::Result<()> {
-    let mut terminal = ratatui::init();
-
-    let mut is_running = APPLICATION
-        .read()
-        .unwrap()
-        .as_ref()
-        .expect("APPLICATION is None. Did you run app::init()?")
-        .is_running;
-
-    while is_running {
-        unsafe {
-            if _CHANGE_VIEW {
-                _CHANGE_VIEW = false;
-                let mut mainpage = VIEW.write().expect("create custom error here");
-                *mainpage = Some(_NEXT_VIEW.take().unwrap());
-                _NEXT_VIEW = None;
-            }
-        }
+/// - [`Error::NotInitialized`] if [`init()`] was not run.
+/// - [`Error::Io`] if any error occurs while drawing or while handling the events.
+pub fn run() -> Result<(), Error> {
+    take_default_app()?.run()
+}
 
-        terminal.draw(|frame: &mut ratatui::Frame<'_>| {
-            ViewWidgetWrapper(&VIEW.read().unwrap().as_ref().unwrap())
-                .render_ref(frame.area(), frame.buffer_mut());
-        })?;
+/// Start running the default application loop and return the value provided to [`AppContext::quit_with()`].
+/// See [`App::run_with_result()`].
+///
+/// NOTE: This function MUST be run after [`init()`].
+pub fn run_with_result<T: Any>() -> Result<Option<T>, Error> {
+    take_default_app()?.run_with_result()
+}
 
-        VIEW.write()
-            .unwrap()
-            .as_mut()
-            .unwrap()
-            .handle_events(&event::read()?)?;
+/// Start running the default application loop asynchronously. See [`App::run_async()`].
+///
+/// NOTE: This function MUST be run after [`init()`].
+#[cfg(feature = "tokio")]
+pub async fn run_async() -> Result<(), Error> {
+    take_default_app()?.run_async().await
+}
 
-        is_running = APPLICATION
-            .read()
-            .unwrap()
-            .as_ref()
-            .expect("APPLICATION is None. Did you run app::init()?")
-            .is_running;
-    }
+/// Returns [`AppContext`] of the default application.
+///
+/// NOTE: This function MUST be run after [`init()`].
+///
+/// # Returns:
+/// - `Ok(context)` of the default application.
+/// - [`Error::NotInitialized`] if [`init()`] was not run.
+pub fn context() -> Result<AppContext, Error> {
+    DEFAULT_CONTEXT.read()?.clone().ok_or(Error::NotInitialized)
+}
+
+/// Returns [`Sender`] posting messages into the default application loop. See [`AppContext::sender()`].
+///
+/// NOTE: This function MUST be run after [`init()`].
+pub fn sender() -> Result<Sender, Error> {
+    Ok(context()?.sender())
+}
 
-    ratatui::restore();
+/// Exits out of the default application loop on the next iteration. See [`AppContext::quit()`].
+///
+/// NOTE: This function MUST be run after [`init()`].
+pub fn quit() -> Result<(), Error> {
+    context()?.quit();
     Ok(())
 }
 
-/// Changes current view to the new provided at the beggining of the next application running loop.
-/// 
+/// Shows a toast notification in the default application. See [`AppContext::notify()`].
+///
 /// NOTE: This function MUST be run after [`init()`].
-/// 
+pub fn notify(level: Level, message: impl Into<String>) -> Result<(), Error> {
+    context()?.notify(level, message);
+    Ok(())
+}
+
+/// Changes current view to the new provided at the beginning of the next application running loop.
+///
+/// Current view is dropped. Same as [`replace_view()`].
+///
+/// NOTE: This function MUST be run after [`init()`].
+///
 /// # Parameters:
-/// - `view`: A struct implementing the [`View`] trait. Represents application view that will override current [`VIEW`].
-pub fn change_view<T: View + Sync + Send + 'static>(view: T) {
-    if APPLICATION.read().unwrap().is_none(){
-        panic!("APPLICATION is None. Did you run app::init()?")
-    }
+/// - `view`: A struct implementing the [`View`] trait. Represents application view that will override current view.
+pub fn change_view<T: View + Sync + Send + 'static>(view: T) -> Result<(), Error> {
+    context()?.change_view(view);
+    Ok(())
+}
 
-    unsafe {
-        if _CHANGE_VIEW {
-            return;
-        }
-        _CHANGE_VIEW = true;
-        _NEXT_VIEW = Some(Box::new(view));
-    }
-}
\ No newline at end of file
+/// Suspends current view and shows the new provided on top of it at the beginning of the next application running loop.
+///
+/// Suspended view keeps its state and is resumed by [`pop_view()`] or [`pop_to_root()`].
+///
+/// NOTE: This function MUST be run after [`init()`].
+///
+/// # Parameters:
+/// - `view`: A struct implementing the [`View`] trait. Represents application view that will become current view.
+pub fn push_view<T: View + Sync + Send + 'static>(view: T) -> Result<(), Error> {
+    context()?.push_view(view);
+    Ok(())
+}
+
+/// Drops current view and resumes the most recently suspended one at the beginning of the next application running loop.
+///
+/// Does nothing if there is no suspended view.
+///
+/// NOTE: This function MUST be run after [`init()`].
+pub fn pop_view() -> Result<(), Error> {
+    context()?.pop_view();
+    Ok(())
+}
+
+/// Replaces current view with the new provided at the beginning of the next application running loop.
+///
+/// Current view is dropped, suspended views are left untouched.
+///
+/// NOTE: This function MUST be run after [`init()`].
+///
+/// # Parameters:
+/// - `view`: A struct implementing the [`View`] trait. Represents application view that will override current view.
+pub fn replace_view<T: View + Sync + Send + 'static>(view: T) -> Result<(), Error> {
+    context()?.replace_view(view);
+    Ok(())
+}
+
+/// Drops current view and all suspended views except the first one, which is resumed at the beginning of the next application running loop.
+///
+/// Does nothing if there is no suspended view.
+///
+/// NOTE: This function MUST be run after [`init()`].
+pub fn pop_to_root() -> Result<(), Error> {
+    context()?.pop_to_root();
+    Ok(())
+}
+
+/// Creates a view registered with [`router::register()`](crate::router::register) under route matching the provided
+/// path and pushes it on top of the navigation stack like [`push_view()`].
+///
+/// NOTE: This function MUST be run after [`init()`].
+///
+/// # Parameters:
+/// - `path`: Path to navigate to, ex. `"/users/42/edit"`.
+///
+/// # Returns:
+/// - `Ok(())` if the view was created and will be shown.
+/// - [`Error::NotInitialized`] if [`init()`] was not run.
+/// - [`Error::RouteNotFound`] if no route matches the path.
+pub fn navigate(path: &str) -> Result<(), Error> {
+    context()?.navigate(path)
+}
@@ -0,0 +1,451 @@
+//! See [`AppContext`].
+
+#[cfg(feature = "tokio")]
+use super::crash::Scoped;
+use super::{inbox::Inbox, Level, Message, Overlay, Sender};
+use crate::{
+    router::{self, Router},
+    view::{BoxedView, View},
+    Error,
+};
+use ratatui::text::Text;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError, RwLock,
+    },
+};
+
+/// A cloneable handle to the running application.
+///
+/// Context is passed to [`View::handle_events()`] and can be cloned and moved to other threads. It allows to:
+/// - quit the application with [`AppContext::quit()`] or return a value from it with [`AppContext::quit_with()`],
+/// - navigate between views with [`AppContext::push_view()`], [`AppContext::pop_view()`] and others,
+/// - access state shared between views with [`AppContext::set_state()`] and [`AppContext::with_state()`],
+/// - post messages from other threads with [`AppContext::sender()`],
+/// - show toast notifications with [`AppContext::notify()`].
+///
+/// All navigation requests are queued and applied in order at the beginning of the next application running loop.
+#[derive(Clone)]
+pub struct AppContext {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    running: AtomicBool,
+    exit_value: Mutex<Option<Box<dyn Any + Send>>>,
+    navigation: Mutex<Vec<Navigation>>,
+    state: Mutex<HashMap<TypeId, Box<dyn Any + Send>>>,
+    inserted: Mutex<Vec<Text<'static>>>,
+    /// Toasts requested with [`AppContext::notify()`], shown before the next frame is drawn.
+    notifications: Mutex<Vec<(Level, String)>>,
+    pending_keys: Mutex<Option<String>>,
+    /// Mode of the active view, see [`AppContext::mode()`].
+    mode: Mutex<Option<String>>,
+    /// Routes resolved by [`AppContext::navigate()`], `None` in the default application which uses routes registered
+    /// with [`router::register()`].
+    router: RwLock<Option<Router>>,
+    inbox: Arc<Inbox>,
+}
+
+pub(crate) enum Navigation {
+    Push(BoxedView),
+    Pop,
+    Replace(BoxedView),
+    PopToRoot,
+    /// Changes mode of the active view.
+    SetMode(String),
+    OpenOverlay(Overlay),
+    /// Closes the top overlay and delivers the message to the view or overlay below.
+    CloseOverlay(Option<Message>),
+}
+
+impl AppContext {
+    pub(crate) fn new() -> Self {
+        AppContext {
+            shared: Arc::new(Shared {
+                running: AtomicBool::new(true),
+                exit_value: Mutex::new(None),
+                navigation: Mutex::new(Vec::new()),
+                state: Mutex::new(HashMap::new()),
+                inserted: Mutex::new(Vec::new()),
+                notifications: Mutex::new(Vec::new()),
+                pending_keys: Mutex::new(None),
+                mode: Mutex::new(None),
+                router: RwLock::new(Some(Router::new())),
+                inbox: Arc::new(Inbox::new()),
+            }),
+        }
+    }
+
+    /// Returns `false` once the application was requested to quit.
+    pub fn is_running(&self) -> bool {
+        self.shared.running.load(Ordering::SeqCst)
+    }
+
+    /// Exits out of the application loop on the next iteration.
+    pub fn quit(&self) {
+        self.shared.running.store(false, Ordering::SeqCst);
+        self.shared.inbox.wake();
+    }
+
+    /// Exits out of the application loop on the next iteration and returns the provided value from
+    /// [`App::run_with_result()`](crate::app::App::run_with_result).
+    ///
+    /// Value can be anything, ex. an item chosen by the user or an exit code.
+    ///
+    /// ```
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::App, view::View};
+    /// # use std::process::ExitCode;
+    /// # struct MainPage;
+    /// # impl View for MainPage {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    /// # }
+    /// let ctx = App::new(MainPage).context();
+    /// ctx.quit_with(ExitCode::from(2));
+    ///
+    /// assert!(!ctx.is_running());
+    /// ```
+    pub fn quit_with<T: Any + Send>(&self, value: T) {
+        *self
+            .shared
+            .exit_value
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Box::new(value));
+        self.quit();
+    }
+
+    /// Changes current view to the new provided. Current view is dropped. Same as [`AppContext::replace_view()`].
+    pub fn change_view<T: View + Sync + Send + 'static>(&self, view: T) {
+        self.replace_view(view);
+    }
+
+    /// Suspends current view and shows the new provided on top of it.
+    ///
+    /// Suspended view keeps its state and is resumed by [`AppContext::pop_view()`] or [`AppContext::pop_to_root()`].
+    pub fn push_view<T: View + Sync + Send + 'static>(&self, view: T) {
+        self.navigate_with(Navigation::Push(Box::new(view)));
+    }
+
+    /// Drops current view and resumes the most recently suspended one. Does nothing if there is no suspended view.
+    pub fn pop_view(&self) {
+        self.navigate_with(Navigation::Pop);
+    }
+
+    /// Replaces current view with the new provided. Current view is dropped, suspended views are left untouched.
+    pub fn replace_view<T: View + Sync + Send + 'static>(&self, view: T) {
+        self.navigate_with(Navigation::Replace(Box::new(view)));
+    }
+
+    /// Drops current view and all suspended views except the first one, which is resumed.
+    /// Does nothing if there is no suspended view.
+    pub fn pop_to_root(&self) {
+        self.navigate_with(Navigation::PopToRoot);
+    }
+
+    /// Shows the overlay above the active view and other overlays. See [`Overlay`].
+    ///
+    /// Overlay is closed when the view which opened it leaves the top of the navigation stack.
+    pub fn open_overlay(&self, overlay: Overlay) {
+        self.navigate_with(Navigation::OpenOverlay(overlay));
+    }
+
+    /// Closes the top overlay without a result. Does nothing if no overlay is open.
+    pub fn close_overlay(&self) {
+        self.navigate_with(Navigation::CloseOverlay(None));
+    }
+
+    /// Closes the top overlay and delivers the value as a [`Message`] to [`View::handle_message()`] of the view or
+    /// overlay below, which opened it. Does nothing if no overlay is open.
+    pub fn close_overlay_with<T: Any + Send>(&self, value: T) {
+        self.navigate_with(Navigation::CloseOverlay(Some(Message::new(value))));
+    }
+
+    /// Creates a view registered in the [`Router`] set with [`App::router()`](super::App::router) under route matching
+    /// the provided path and pushes it on top of the navigation stack like [`AppContext::push_view()`].
+    ///
+    /// The default application created by [`app::init()`](super::init) resolves routes registered with
+    /// [`router::register()`] instead.
+    ///
+    /// # Returns:
+    /// - `Ok(())` if the view was created and will be shown.
+    /// - [`Error::RouteNotFound`] if no route matches the path.
+    pub fn navigate(&self, path: &str) -> Result<(), Error> {
+        // Factory is called after the router is unlocked, so it can register routes.
+        let route = match &*self
+            .shared
+            .router
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            Some(router) => router.find(path),
+            None => router::find(path),
+        }?;
+        self.navigate_with(Navigation::Push(route.create()));
+        Ok(())
+    }
+
+    /// Stores the provided value as application state of type `S`, replacing previous value of the same type.
+    pub fn set_state<S: Any + Send>(&self, state: S) {
+        self.shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(TypeId::of::<S>(), Box::new(state));
+    }
+
+    /// Calls `f` with mutable reference to the application state of type `S`.
+    ///
+    /// If a previous call to `f` panicked, `f` receives the state as that call left it.
+    ///
+    /// # Returns:
+    /// - `Ok(result)` of `f` if state of type `S` was set with [`AppContext::set_state()`].
+    /// - [`Error::StateMissing`] if state of type `S` was not set.
+    ///
+    /// ```
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::App, view::View};
+    /// # struct MainPage;
+    /// # impl View for MainPage {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    /// # }
+    /// struct Counter(u32);
+    ///
+    /// let ctx = App::new(MainPage).with_state(Counter(0)).context();
+    /// ctx.with_state(|counter: &mut Counter| counter.0 += 1)?;
+    ///
+    /// assert_eq!(ctx.with_state(|counter: &mut Counter| counter.0)?, 1);
+    /// assert!(ctx.with_state(|_: &mut String| ()).is_err());
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    pub fn with_state<S: Any + Send, R>(&self, f: impl FnOnce(&mut S) -> R) -> Result<R, Error> {
+        let mut state = self
+            .shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        state
+            .get_mut(&TypeId::of::<S>())
+            .and_then(|state| state.downcast_mut::<S>())
+            .map(f)
+            .ok_or(Error::StateMissing(std::any::type_name::<S>()))
+    }
+
+    /// Prints the text above the inline viewport set with [`App::viewport()`](super::App::viewport), where it stays
+    /// in the scrollback, ex. to log finished steps of a progress view. Text is printed before the next frame is drawn.
+    ///
+    /// Does nothing if application does not run in [`Viewport::Inline`](ratatui::Viewport::Inline).
+    ///
+    /// ```no_run
+    /// # use ratatui::{buffer::Buffer, layout::Rect, style::Stylize, Viewport};
+    /// # use ratatuio::{app::{App, AppContext}, view::View};
+    /// # use std::{io, time::Duration};
+    /// struct Progress {
+    ///     done: u32,
+    /// }
+    ///
+    /// impl View for Progress {
+    /// #   fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    ///     fn on_tick(&mut self, _elapsed: Duration, ctx: &AppContext) -> io::Result<()> {
+    ///         self.done += 1;
+    ///         ctx.insert_before(format!("step {} finished", self.done).green());
+    ///         if self.done == 10 {
+    ///             ctx.quit();
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// App::new(Progress { done: 0 })
+    ///     .viewport(Viewport::Inline(1))
+    ///     .tick_rate(Duration::from_millis(200))
+    ///     .run()?;
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    pub fn insert_before(&self, text: impl Into<Text<'static>>) {
+        self.shared
+            .inserted
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(text.into());
+        self.shared.inbox.wake();
+    }
+
+    /// Shows a toast notification with the message in the corner set with
+    /// [`App::toast_corner()`](super::App::toast_corner), above the view and overlays. Can be called from any thread.
+    ///
+    /// Toasts stack, the newest nearest to the corner, and each is hidden after
+    /// [`App::toast_timeout()`](super::App::toast_timeout) or with [`App::dismiss_toast_key()`](super::App::dismiss_toast_key).
+    ///
+    /// ```
+    /// # use crossterm::event::KeyCode;
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::{App, Corner, Level}, testing::Harness, view::View};
+    /// # use std::thread;
+    /// # struct MainPage;
+    /// # impl View for MainPage {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    /// # }
+    /// let app = App::new(MainPage)
+    ///     .toast_corner(Corner::BottomRight)
+    ///     .dismiss_toast_key(KeyCode::Esc);
+    /// let mut harness = Harness::new(app, 20, 4)?;
+    ///
+    /// let ctx = harness.context();
+    /// thread::spawn(move || ctx.notify(Level::Success, "Saved")).join().unwrap();
+    /// harness.run()?;
+    /// assert_eq!(
+    ///     harness.lines(),
+    ///     [
+    ///         "                    ",
+    ///         "           ╭───────╮",
+    ///         "           │ Saved │",
+    ///         "           ╰───────╯",
+    ///     ]
+    /// );
+    ///
+    /// harness.key(KeyCode::Esc).run()?;
+    /// assert!(harness.lines().iter().all(|line| line.trim().is_empty()));
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    pub fn notify(&self, level: Level, message: impl Into<String>) {
+        self.shared
+            .notifications
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((level, message.into()));
+        self.shared.inbox.wake();
+    }
+
+    /// Changes mode of the active view, ex. `"insert"` when a vim-like editor starts inserting text. Mode is applied
+    /// in order with navigation requests, then [`View::on_mode_change()`] of the view is called.
+    ///
+    /// Every view on the navigation stack keeps its own mode, which is `None` until it is set. Bindings of the
+    /// [`Keymap`](crate::keymap::Keymap) can be scoped to modes, see [modes](crate::keymap#modes).
+    pub fn set_mode(&self, mode: impl Into<String>) {
+        self.navigate_with(Navigation::SetMode(mode.into()));
+    }
+
+    /// Returns mode of the active view set with [`AppContext::set_mode()`], ex. to show it in a status line.
+    pub fn mode(&self) -> Option<String> {
+        self.shared
+            .mode
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns count and keys of an unfinished key sequence of the [`Keymap`](crate::keymap::Keymap), ex. `"2g"`
+    /// after pressing `2` and `g` while `g g` is bound, or `None` if no sequence is pending.
+    ///
+    /// Views can render it in a status line, so users see that the application waits for more keys.
+    pub fn pending_keys(&self) -> Option<String> {
+        self.shared
+            .pending_keys
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns a [`Sender`] posting messages into the application loop, which can be moved to other threads.
+    pub fn sender(&self) -> Sender {
+        Sender::new(self.shared.inbox.clone())
+    }
+
+    /// Spawns the future on the tokio runtime and posts its output as a [`Message`](super::Message) into the
+    /// application loop, where it is delivered to [`View::handle_message()`](crate::view::View::handle_message).
+    ///
+    /// Requires `tokio` feature and must be called from within a tokio runtime, ex. from a view of application
+    /// started with [`App::run_async()`](super::App::run_async). If the future panics, the terminal is restored and the
+    /// application loop exits with an error.
+    #[cfg(feature = "tokio")]
+    pub fn spawn<F>(&self, future: F) -> tokio::task::JoinHandle<()>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Any + Send,
+    {
+        let sender = self.sender();
+        tokio::spawn(Scoped::new(self.shared.inbox.clone(), async move {
+            let _ = sender.send(future.await);
+        }))
+    }
+
+    pub(crate) fn inbox(&self) -> &Arc<Inbox> {
+        &self.shared.inbox
+    }
+
+    fn navigate_with(&self, navigation: Navigation) {
+        self.shared
+            .navigation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(navigation);
+        self.shared.inbox.wake();
+    }
+
+    pub(crate) fn take_exit_value(&self) -> Option<Box<dyn Any + Send>> {
+        self.shared
+            .exit_value
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+
+    pub(crate) fn take_inserted(&self) -> Vec<Text<'static>> {
+        std::mem::take(
+            &mut *self
+                .shared
+                .inserted
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+
+    pub(crate) fn take_notifications(&self) -> Vec<(Level, String)> {
+        std::mem::take(
+            &mut *self
+                .shared
+                .notifications
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+
+    /// Sets routes resolved by [`AppContext::navigate()`], `None` to use routes registered with [`router::register()`].
+    pub(crate) fn set_router(&self, router: Option<Router>) {
+        *self
+            .shared
+            .router
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = router;
+    }
+
+    pub(crate) fn set_active_mode(&self, mode: Option<String>) {
+        *self
+            .shared
+            .mode
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = mode;
+    }
+
+    pub(crate) fn set_pending_keys(&self, keys: Option<String>) {
+        *self
+            .shared
+            .pending_keys
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = keys;
+    }
+
+    pub(crate) fn take_navigation(&self) -> Vec<Navigation> {
+        std::mem::take(
+            &mut *self
+                .shared
+                .navigation
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+}
@@ -0,0 +1,426 @@
+//! Guaranteed terminal restoration after errors and panics, see [`CrashReport`].
+
+use super::inbox::{Inbox, Signal};
+use crate::{
+    backend::{Restorer, TerminalBackend, TerminalConfig},
+    Error,
+};
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    cell::Cell,
+    fmt, io, mem,
+    panic::{self, Location},
+    ptr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Once, PoisonError,
+    },
+    thread::{self, ThreadId},
+};
+
+pub(crate) type CrashHandler = Arc<dyn Fn(&CrashReport) + Send + Sync>;
+
+/// Details of a crash passed to the handler set with [`App::on_crash()`](super::App::on_crash).
+///
+/// Handler is called after the terminal was restored, so it can safely print or log the report.
+#[derive(Debug)]
+pub struct CrashReport {
+    /// Panic payload or error message.
+    pub message: String,
+    /// Source location of the panic, `None` for errors.
+    pub location: Option<String>,
+    /// `true` if a view panicked, `false` if application loop exited with an error.
+    pub panicked: bool,
+    /// Backtrace captured when the crash occurred, according to `RUST_BACKTRACE` environment variable.
+    pub backtrace: Backtrace,
+}
+
+impl CrashReport {
+    fn from_panic(payload: &(dyn Any + Send), location: Option<&Location<'_>>) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_owned());
+
+        CrashReport {
+            message,
+            location: location.map(|location| location.to_string()),
+            panicked: true,
+            backtrace: Backtrace::capture(),
+        }
+    }
+
+    fn from_error(err: &Error) -> Self {
+        CrashReport {
+            message: err.to_string(),
+            location: None,
+            panicked: false,
+            backtrace: Backtrace::capture(),
+        }
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.location, self.panicked) {
+            (Some(location), _) => {
+                write!(f, "application panicked at {location}:\n{}", self.message)?
+            }
+            (None, true) => write!(f, "application panicked:\n{}", self.message)?,
+            (None, false) => write!(f, "application exited with error:\n{}", self.message)?,
+        }
+        write!(f, "\n{}", self.backtrace)
+    }
+}
+
+/// Terminals currently owned by running applications.
+static ACTIVE: Mutex<Vec<ActiveTerminal>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static INSTALL_HOOK: Once = Once::new();
+
+thread_local! {
+    /// Inbox of the application whose task is being polled on this thread, see [`Scoped`].
+    static TASK_INBOX: Cell<*const Inbox> = const { Cell::new(ptr::null()) };
+}
+
+struct ActiveTerminal {
+    id: u64,
+    thread: ThreadId,
+    handler: Option<CrashHandler>,
+    restorer: Restorer,
+    inbox: Arc<Inbox>,
+}
+
+impl ActiveTerminal {
+    fn is_owned_by(&self, thread: ThreadId, task_inbox: *const Inbox) -> bool {
+        self.thread == thread || ptr::eq(Arc::as_ptr(&self.inbox), task_inbox)
+    }
+}
+
+/// Prepares the terminal with [`TerminalBackend::setup()`], and restores it when dropped.
+///
+/// While the guard is alive, panic on the thread that created it or in a task spawned with
+/// [`AppContext::spawn()`](super::AppContext::spawn) restores the terminal before the panic message is printed.
+/// Panic on a thread owned by no application restores all terminals, because it cannot be told which one it broke.
+/// Loops of applications restored after a panic on another thread exit with an error.
+pub(crate) struct TerminalGuard {
+    id: u64,
+    handler: Option<CrashHandler>,
+    restorer: Restorer,
+}
+
+impl TerminalGuard {
+    pub(crate) fn enter<B: TerminalBackend>(
+        backend: &mut B,
+        config: &TerminalConfig,
+        handler: Option<CrashHandler>,
+        inbox: Arc<Inbox>,
+    ) -> io::Result<Self> {
+        INSTALL_HOOK.call_once(install_panic_hook);
+
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        let restorer = backend.restorer(config);
+        active().push(ActiveTerminal {
+            id,
+            thread: thread::current().id(),
+            handler: handler.clone(),
+            restorer: restorer.clone(),
+            inbox,
+        });
+
+        let guard = TerminalGuard {
+            id,
+            handler,
+            restorer,
+        };
+        backend.setup(config)?;
+        Ok(guard)
+    }
+
+    /// Restores the terminal and reports the error to the crash handler, unless the panic hook already restored the
+    /// terminal and reported the panic.
+    pub(crate) fn exit<T>(self, result: &Result<T, Error>) {
+        if !self.release() {
+            return;
+        }
+
+        if let (Err(err), Some(handler)) = (result, &self.handler) {
+            handler(&CrashReport::from_error(err));
+        }
+    }
+
+    /// Restores the terminal. Returns `false` if it was already restored by the panic hook.
+    fn release(&self) -> bool {
+        let mut active = active();
+        let Some(index) = active.iter().position(|terminal| terminal.id == self.id) else {
+            return false;
+        };
+        active.remove(index);
+        drop(active);
+
+        restore(&self.restorer);
+        true
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// Future of a task spawned with [`AppContext::spawn()`](super::AppContext::spawn), which attributes panics of the
+/// task to the application that spawned it, whichever thread polls it.
+#[cfg(feature = "tokio")]
+pub(crate) struct Scoped<F> {
+    inbox: Arc<Inbox>,
+    future: std::pin::Pin<Box<F>>,
+}
+
+#[cfg(feature = "tokio")]
+impl<F> Scoped<F> {
+    pub(crate) fn new(inbox: Arc<Inbox>, future: F) -> Self {
+        Scoped {
+            inbox,
+            future: Box::pin(future),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<F: std::future::Future> std::future::Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<F::Output> {
+        struct Reset(*const Inbox);
+
+        impl Drop for Reset {
+            fn drop(&mut self) {
+                TASK_INBOX.set(self.0);
+            }
+        }
+
+        let _reset = Reset(TASK_INBOX.replace(Arc::as_ptr(&self.inbox)));
+        self.future.as_mut().poll(cx)
+    }
+}
+
+fn active() -> std::sync::MutexGuard<'static, Vec<ActiveTerminal>> {
+    ACTIVE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn restore(restorer: &Restorer) {
+    if let Err(err) = restorer() {
+        eprintln!("Failed to restore terminal: {err}");
+    }
+}
+
+fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let current = thread::current().id();
+        let task_inbox = TASK_INBOX.try_with(Cell::get).unwrap_or(ptr::null());
+        let crashed: Vec<ActiveTerminal> = {
+            let mut active = active();
+            if active
+                .iter()
+                .any(|terminal| terminal.is_owned_by(current, task_inbox))
+            {
+                let (crashed, running) = active
+                    .drain(..)
+                    .partition(|terminal| terminal.is_owned_by(current, task_inbox));
+                *active = running;
+                crashed
+            } else {
+                mem::take(&mut *active)
+            }
+        };
+
+        for terminal in &crashed {
+            restore(&terminal.restorer);
+        }
+
+        previous(info);
+
+        if crashed.iter().any(|terminal| terminal.handler.is_some()) {
+            let report = CrashReport::from_panic(info.payload(), info.location());
+            for handler in crashed
+                .iter()
+                .filter_map(|terminal| terminal.handler.as_ref())
+            {
+                handler(&report);
+            }
+        }
+
+        // Loops running on other threads would keep drawing to the restored terminal.
+        for terminal in &crashed {
+            terminal.inbox.push(Signal::Crashed);
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::{App, AppContext},
+        backend::{Restorer, TerminalBackend, TerminalConfig},
+        recording::Recording,
+        view::View,
+    };
+    use crossterm::event::{Event, KeyCode};
+    use ratatui::{
+        backend::{Backend, ClearType, TestBackend, WindowSize},
+        buffer::{Buffer, Cell},
+        layout::{Position, Rect, Size},
+    };
+    use std::{
+        io,
+        panic::{self, AssertUnwindSafe},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    /// Test backend recording whether its restorer was called.
+    struct RestoredBackend {
+        inner: TestBackend,
+        restored: Arc<AtomicBool>,
+    }
+
+    impl Backend for RestoredBackend {
+        fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+        where
+            I: Iterator<Item = (u16, u16, &'a Cell)>,
+        {
+            self.inner.draw(content)
+        }
+
+        fn hide_cursor(&mut self) -> io::Result<()> {
+            self.inner.hide_cursor()
+        }
+
+        fn show_cursor(&mut self) -> io::Result<()> {
+            self.inner.show_cursor()
+        }
+
+        fn get_cursor_position(&mut self) -> io::Result<Position> {
+            self.inner.get_cursor_position()
+        }
+
+        fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+            self.inner.set_cursor_position(position)
+        }
+
+        fn clear(&mut self) -> io::Result<()> {
+            self.inner.clear()
+        }
+
+        fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+            self.inner.clear_region(clear_type)
+        }
+
+        fn size(&self) -> io::Result<Size> {
+            self.inner.size()
+        }
+
+        fn window_size(&mut self) -> io::Result<WindowSize> {
+            self.inner.window_size()
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    impl TerminalBackend for RestoredBackend {
+        fn setup(&mut self, _config: &TerminalConfig) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn restorer(&self, _config: &TerminalConfig) -> Restorer {
+            let restored = self.restored.clone();
+            Arc::new(move || {
+                restored.store(true, Ordering::SeqCst);
+                Ok(())
+            })
+        }
+
+        fn reads_events(&self) -> bool {
+            false
+        }
+    }
+
+    /// Returns application using [`RestoredBackend`], its restore flag and panics reported to its crash handler.
+    fn crashing_app(
+        view: impl View + Send + Sync + 'static,
+    ) -> (App, Arc<AtomicBool>, Arc<Mutex<Vec<bool>>>) {
+        let restored = Arc::new(AtomicBool::new(false));
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let backend = RestoredBackend {
+            inner: TestBackend::new(10, 2),
+            restored: restored.clone(),
+        };
+        let mut recording = Recording::new();
+        recording.push(Duration::ZERO, Event::Key(KeyCode::Enter.into()));
+
+        let handler_reports = reports.clone();
+        let app = App::new(view)
+            .with_backend(backend)
+            .replay(recording)
+            .on_crash(move |report| handler_reports.lock().unwrap().push(report.panicked));
+        (app, restored, reports)
+    }
+
+    struct PanickingView;
+
+    impl View for PanickingView {
+        fn render_view(&self, _area: Rect, _buf: &mut Buffer) {}
+
+        fn handle_events(&mut self, _event: &Event, _ctx: &AppContext) -> io::Result<()> {
+            panic!("view failed");
+        }
+    }
+
+    #[test]
+    fn panicking_view_restores_terminal() {
+        let (app, restored, reports) = crashing_app(PanickingView);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| app.run()));
+
+        assert!(result.is_err());
+        assert!(restored.load(Ordering::SeqCst));
+        assert_eq!(*reports.lock().unwrap(), [true]);
+    }
+
+    #[cfg(feature = "tokio")]
+    struct SpawningView;
+
+    #[cfg(feature = "tokio")]
+    impl View for SpawningView {
+        fn render_view(&self, _area: Rect, _buf: &mut Buffer) {}
+
+        fn handle_events(&mut self, _event: &Event, ctx: &AppContext) -> io::Result<()> {
+            ctx.spawn(async { panic!("task failed") });
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn panicking_task_on_worker_thread_restores_terminal() {
+        let (app, restored, reports) = crashing_app(SpawningView);
+
+        let result = app.run_async().await;
+
+        assert!(result.is_err());
+        assert!(restored.load(Ordering::SeqCst));
+        assert_eq!(*reports.lock().unwrap(), [true]);
+    }
+}
@@ -0,0 +1,174 @@
+//! Help overlay listing key bindings of the active view, see [`App::help_key()`](super::App::help_key).
+
+use crate::keymap::{format_sequence, Keymap};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Widget},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// State of the open help overlay.
+#[derive(Debug, Default)]
+pub(crate) struct Help {
+    /// Text typed by the user, only bindings containing it are listed.
+    query: String,
+    /// Index of the first listed line.
+    scroll: usize,
+}
+
+impl Help {
+    /// Handles the event while help is open, so it does not reach the view.
+    ///
+    /// Returns `false` if help should be closed.
+    pub(crate) fn handle_event(&mut self, event: &Event) -> bool {
+        let key = match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key,
+            Event::Paste(text) => {
+                self.search(|query| query.push_str(text));
+                return true;
+            }
+            _ => return true,
+        };
+
+        match key.code {
+            KeyCode::Esc => return false,
+            KeyCode::Backspace => self.search(|query| {
+                query.pop();
+            }),
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll += 1,
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll += 10,
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.search(|query| query.push(c))
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Returns widget listing bindings of the contexts matching the query, grouped by context.
+    pub(crate) fn widget(&mut self, keymap: &Keymap, scopes: &[String]) -> HelpWidget {
+        let query = self.query.to_lowercase();
+        let groups: Vec<(&String, Vec<(String, &str)>)> = scopes
+            .iter()
+            .map(|scope| {
+                let rows = keymap
+                    .bindings(scope)
+                    .map(|(action, sequences)| {
+                        let keys = sequences
+                            .iter()
+                            .map(|keys| format_sequence(keys))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        (keys, keymap.description(action).unwrap_or(action))
+                    })
+                    .filter(|(keys, description)| {
+                        keys.to_lowercase().contains(&query)
+                            || description.to_lowercase().contains(&query)
+                    })
+                    .collect();
+                (scope, rows)
+            })
+            .filter(|(_, rows): &(_, Vec<_>)| !rows.is_empty())
+            .collect();
+
+        let keys_width = groups
+            .iter()
+            .flat_map(|(_, rows)| rows)
+            .map(|(keys, _)| keys.width())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = Vec::new();
+        for (scope, rows) in groups {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::from(scope.clone().bold().underlined()));
+            for (keys, description) in rows {
+                let padding = " ".repeat(keys_width - keys.width());
+                lines.push(Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(keys, Style::new().cyan()),
+                    Span::raw(padding + "  "),
+                    Span::raw(description.to_owned()),
+                ]));
+            }
+        }
+        if lines.is_empty() {
+            lines.push(Line::from("No bindings found".italic()));
+        }
+
+        self.scroll = self.scroll.min(lines.len() - 1);
+        HelpWidget {
+            lines,
+            query: self.query.clone(),
+            scroll: self.scroll,
+        }
+    }
+
+    fn search(&mut self, edit: impl FnOnce(&mut String)) {
+        edit(&mut self.query);
+        self.scroll = 0;
+    }
+}
+
+/// Help overlay drawn on top of the active view.
+pub(crate) struct HelpWidget {
+    lines: Vec<Line<'static>>,
+    query: String,
+    scroll: usize,
+}
+
+impl Widget for &HelpWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = four_fifths(area.width).max(area.width.min(30));
+        let height = four_fifths(area.height).max(area.height.min(6));
+        let area = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        let search = if self.query.is_empty() {
+            Line::from(" type to search, esc to close ".dim())
+        } else {
+            Line::from(format!(" search: {} ", self.query))
+        };
+        let block = Block::bordered()
+            .title(" Help ".bold())
+            .title_bottom(search);
+
+        Clear.render(area, buf);
+        Paragraph::new(self.lines.clone())
+            .block(block)
+            .scroll((u16::try_from(self.scroll).unwrap_or(u16::MAX), 0))
+            .render(area, buf);
+    }
+}
+
+/// Returns 4/5 of the length, computed in `u32` so large terminals do not overflow.
+fn four_fifths(length: u16) -> u16 {
+    (u32::from(length) * 4 / 5) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn help_fits_largest_area() {
+        assert_eq!(four_fifths(u16::MAX), 52428);
+        assert_eq!(four_fifths(9), 7);
+    }
+}
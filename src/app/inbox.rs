@@ -0,0 +1,195 @@
+//! Queue of signals waking the application loop, fed by the terminal input thread, [`Sender`](super::Sender)s and [`AppContext`](super::AppContext).
+
+use super::Message;
+use crate::recording::Recording;
+use crossterm::event::{self, Event};
+use std::{
+    collections::VecDeque,
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// How long the input thread waits for terminal event before checking if it should stop.
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub(crate) enum Signal {
+    Input(Event),
+    InputError(io::Error),
+    Message(Message),
+    /// Requests the loop to apply navigation and redraw.
+    Wake,
+    /// Terminal was restored by the panic hook after a panic outside of the loop, which has to exit.
+    Crashed,
+}
+
+pub(crate) struct Inbox {
+    queue: Mutex<VecDeque<Signal>>,
+    ready: Condvar,
+    #[cfg(feature = "tokio")]
+    notify: tokio::sync::Notify,
+    closed: AtomicBool,
+}
+
+impl Inbox {
+    pub(crate) fn new() -> Self {
+        Inbox {
+            queue: Mutex::new(VecDeque::new()),
+            ready: Condvar::new(),
+            #[cfg(feature = "tokio")]
+            notify: tokio::sync::Notify::new(),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Pushes signal to the queue and wakes the loop. Returns `false` if the inbox was closed.
+    pub(crate) fn push(&self, signal: Signal) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+
+        self.queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_back(signal);
+        self.ready.notify_one();
+        #[cfg(feature = "tokio")]
+        self.notify.notify_one();
+        true
+    }
+
+    pub(crate) fn wake(&self) {
+        self.push(Signal::Wake);
+    }
+
+    /// Waits until at least one signal is queued or until timeout passes, and takes all queued signals.
+    pub(crate) fn wait(&self, timeout: Option<Duration>) -> Vec<Signal> {
+        let queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut queue = match timeout {
+            Some(timeout) => {
+                self.ready
+                    .wait_timeout_while(queue, timeout, |queue| queue.is_empty())
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0
+            }
+            None => self
+                .ready
+                .wait_while(queue, |queue| queue.is_empty())
+                .unwrap_or_else(PoisonError::into_inner),
+        };
+
+        queue.drain(..).collect()
+    }
+
+    /// Waits asynchronously until at least one signal is queued and takes all queued signals.
+    #[cfg(feature = "tokio")]
+    pub(crate) async fn wait_async(&self) -> Vec<Signal> {
+        loop {
+            let signals: Vec<Signal> = self
+                .queue
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .drain(..)
+                .collect();
+            if !signals.is_empty() {
+                return signals;
+            }
+
+            self.notify.notified().await;
+        }
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+/// Thread reading terminal events and pushing them to the [`Inbox`]. Stops when dropped.
+///
+/// If a recording is provided, its events are pushed first at their recorded times, and terminal events are read only
+/// after the whole recording was replayed. Terminal events are not read at all if `read_terminal` is `false`.
+pub(crate) struct InputReader {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl InputReader {
+    pub(crate) fn spawn(inbox: Arc<Inbox>, replay: Option<Recording>, read_terminal: bool) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let handle = thread::spawn(move || {
+            if let Some(recording) = replay {
+                if !replay_recording(&inbox, recording, &thread_stop) {
+                    return;
+                }
+            }
+
+            while read_terminal && !thread_stop.load(Ordering::SeqCst) {
+                let signal = match event::poll(INPUT_POLL_INTERVAL) {
+                    Ok(false) => continue,
+                    Ok(true) => match event::read() {
+                        Ok(event) => Signal::Input(event),
+                        Err(err) => Signal::InputError(err),
+                    },
+                    Err(err) => Signal::InputError(err),
+                };
+
+                let failed = matches!(signal, Signal::InputError(_));
+                if !inbox.push(signal) || failed {
+                    break;
+                }
+            }
+        });
+
+        InputReader {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+/// Pushes recorded events at their recorded times. Returns `false` if the reader was stopped or the inbox closed.
+fn replay_recording(inbox: &Inbox, recording: Recording, stop: &AtomicBool) -> bool {
+    let start = Instant::now();
+
+    for recorded in recording.events {
+        loop {
+            if stop.load(Ordering::SeqCst) {
+                return false;
+            }
+            let remaining = (start + recorded.at).saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            thread::sleep(remaining.min(INPUT_POLL_INTERVAL));
+        }
+
+        if !inbox.push(Signal::Input(recorded.event)) {
+            return false;
+        }
+    }
+
+    true
+}
+
+impl Drop for InputReader {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! See [`Message`] and [`Sender`].
+
+use super::inbox::{Inbox, Signal};
+use crate::Error;
+use std::{any::Any, fmt, sync::Arc};
+
+/// A user-defined message delivered to [`View::handle_message()`](crate::view::View::handle_message).
+///
+/// Message can hold a value of any type, which can be recovered with [`Message::downcast()`] or [`Message::downcast_ref()`].
+pub struct Message(Box<dyn Any + Send>);
+
+impl Message {
+    /// Wraps the provided value in a message.
+    pub fn new<T: Any + Send>(value: T) -> Self {
+        Message(Box::new(value))
+    }
+
+    /// Returns `true` if message holds a value of type `T`.
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+
+    /// Returns reference to the value if it is of type `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+
+    /// Takes the value out of the message.
+    ///
+    /// # Returns:
+    /// - `Ok(value)` if message holds a value of type `T`.
+    /// - `Err(message)` with unchanged message otherwise.
+    pub fn downcast<T: Any>(self) -> Result<T, Message> {
+        self.0.downcast().map(|value| *value).map_err(Message)
+    }
+}
+
+impl fmt::Debug for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Message").finish_non_exhaustive()
+    }
+}
+
+/// A cloneable handle posting [`Message`]s into the application loop from any thread.
+///
+/// Each message wakes the loop, even while it is waiting for terminal input, and is delivered to
+/// [`View::handle_message()`](crate::view::View::handle_message) of the current view.
+///
+/// ```
+/// # use ratatui::{buffer::Buffer, layout::Rect};
+/// # use ratatuio::{app::App, view::View};
+/// # struct MainPage;
+/// # impl View for MainPage {
+/// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+/// # }
+/// struct Indexed(usize);
+///
+/// let sender = App::new(MainPage).context().sender();
+/// std::thread::spawn(move || sender.send(Indexed(42)))
+///     .join()
+///     .unwrap()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Sender {
+    inbox: Arc<Inbox>,
+}
+
+impl Sender {
+    pub(crate) fn new(inbox: Arc<Inbox>) -> Self {
+        Sender { inbox }
+    }
+
+    /// Posts the message into the application loop.
+    ///
+    /// # Returns:
+    /// - `Ok(())` if message was queued.
+    /// - [`Error::Closed`] if application already exited.
+    pub fn send<T: Any + Send>(&self, message: T) -> Result<(), Error> {
+        match self.inbox.push(Signal::Message(Message::new(message))) {
+            true => Ok(()),
+            false => Err(Error::Closed),
+        }
+    }
+
+    /// Returns `true` if application already exited and messages will no longer be delivered.
+    pub fn is_closed(&self) -> bool {
+        self.inbox.is_closed()
+    }
+}
@@ -0,0 +1,180 @@
+//! See [`Middleware`].
+
+use super::AppContext;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::io;
+
+/// Decision of a [`Middleware`] about an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Flow {
+    /// Passes the event, possibly transformed, to the next middleware and then to the active view.
+    Pass(Event),
+    /// Stops the event, neither following middlewares nor the view receive it.
+    Consume,
+}
+
+/// Sees every terminal event before the active view, ex. to handle global key bindings in one place instead of in
+/// every [`View::handle_events()`](crate::view::View::handle_events).
+///
+/// Middlewares are added with [`App::middleware()`](super::App::middleware) and called in order they were added.
+/// Closures taking the event and [`AppContext`] can be used as middlewares too:
+///
+/// ```no_run
+/// # use crossterm::event::{Event, KeyCode};
+/// # use ratatui::{buffer::Buffer, layout::Rect};
+/// # use ratatuio::{app::{App, AppContext, Flow, QuitOnCtrlC}, view::View};
+/// # struct MainPage;
+/// # impl View for MainPage {
+/// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+/// # }
+/// App::new(MainPage)
+///     .middleware(QuitOnCtrlC)
+///     .middleware(|event: Event, ctx: &AppContext| {
+///         if let Event::Key(key) = &event {
+///             if key.code == KeyCode::Char('q') {
+///                 ctx.quit();
+///                 return Ok(Flow::Consume);
+///             }
+///         }
+///         Ok(Flow::Pass(event))
+///     })
+///     .run()?;
+/// # Ok::<(), ratatuio::Error>(())
+/// ```
+pub trait Middleware: Send {
+    /// Handles the event before the active view.
+    ///
+    /// # Returns:
+    /// - `Ok(Flow::Pass(event))` to pass the event, or a transformed one, further.
+    /// - `Ok(Flow::Consume)` to stop the event.
+    /// - [`io::Error`] to exit the application loop with the error, like from a view.
+    fn handle(&mut self, event: Event, ctx: &AppContext) -> io::Result<Flow>;
+}
+
+impl<F> Middleware for F
+where
+    F: FnMut(Event, &AppContext) -> io::Result<Flow> + Send,
+{
+    fn handle(&mut self, event: Event, ctx: &AppContext) -> io::Result<Flow> {
+        self(event, ctx)
+    }
+}
+
+/// Quits the application when `Ctrl+C` is pressed. Since the terminal is in raw mode, `Ctrl+C` does not send
+/// `SIGINT` and is otherwise delivered to the view as a key press.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuitOnCtrlC;
+
+impl Middleware for QuitOnCtrlC {
+    fn handle(&mut self, event: Event, ctx: &AppContext) -> io::Result<Flow> {
+        if is_pressed(
+            &event,
+            &KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+        ) {
+            ctx.quit();
+            return Ok(Flow::Consume);
+        }
+        Ok(Flow::Pass(event))
+    }
+}
+
+/// Application state set by [`DebugToggle`], `true` while debug mode is enabled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DebugMode(pub bool);
+
+/// Toggles [`DebugMode`] state when the key is pressed, so views can show debug information like timings or
+/// internal state without a key binding of their own.
+///
+/// ```
+/// # use crossterm::event::KeyCode;
+/// # use ratatui::{buffer::Buffer, layout::Rect};
+/// # use ratatuio::{app::{App, DebugToggle}, testing::Harness, view::View};
+/// # struct MainPage;
+/// # impl View for MainPage {
+/// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+/// # }
+/// let mut harness = Harness::new(App::new(MainPage).middleware(DebugToggle::new(KeyCode::F(12))), 10, 1)?;
+/// assert!(!DebugToggle::is_enabled(&harness.context()));
+///
+/// harness.key(KeyCode::F(12)).step()?;
+/// assert!(DebugToggle::is_enabled(&harness.context()));
+/// # Ok::<(), ratatuio::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct DebugToggle {
+    key: KeyEvent,
+}
+
+impl DebugToggle {
+    /// Creates middleware toggling debug mode with the key.
+    ///
+    /// # Parameters:
+    /// - `key`: Key code, or key event with modifiers, ex. `KeyEvent::new(KeyCode::Char('d'), KeyModifiers::ALT)`.
+    pub fn new(key: impl Into<KeyEvent>) -> Self {
+        DebugToggle { key: key.into() }
+    }
+
+    /// Returns `true` if debug mode was enabled with the key.
+    pub fn is_enabled(ctx: &AppContext) -> bool {
+        ctx.with_state(|mode: &mut DebugMode| mode.0)
+            .unwrap_or(false)
+    }
+}
+
+impl Middleware for DebugToggle {
+    fn handle(&mut self, event: Event, ctx: &AppContext) -> io::Result<Flow> {
+        if is_pressed(&event, &self.key) {
+            let enabled = DebugToggle::is_enabled(ctx);
+            ctx.set_state(DebugMode(!enabled));
+            return Ok(Flow::Consume);
+        }
+        Ok(Flow::Pass(event))
+    }
+}
+
+/// Returns `true` if the event is press of the key with exactly the same modifiers.
+pub(crate) fn is_pressed(event: &Event, key: &KeyEvent) -> bool {
+    matches!(event, Event::Key(event) if event.kind == KeyEventKind::Press
+        && event.code == key.code
+        && event.modifiers == key.modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app::App, testing::Harness, view::View};
+    use ratatui::{buffer::Buffer, layout::Rect};
+    use std::sync::{Arc, Mutex};
+
+    struct Page {
+        keys: Arc<Mutex<Vec<KeyEvent>>>,
+    }
+
+    impl View for Page {
+        fn render_view(&self, _area: Rect, _buf: &mut Buffer) {}
+
+        fn handle_events(&mut self, event: &Event, _ctx: &AppContext) -> io::Result<()> {
+            if let Event::Key(key) = event {
+                self.keys.lock().unwrap().push(*key);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn ctrl_c_quits_without_reaching_view() {
+        let keys = Arc::new(Mutex::new(Vec::new()));
+        let app = App::new(Page { keys: keys.clone() }).middleware(QuitOnCtrlC);
+        let mut harness = Harness::new(app, 10, 1).unwrap();
+
+        harness
+            .key(KeyCode::Char('c'))
+            .key_with(KeyCode::Char('c'), KeyModifiers::CONTROL)
+            .key(KeyCode::Char('x'))
+            .run()
+            .unwrap();
+
+        assert!(!harness.is_running());
+        assert_eq!(*keys.lock().unwrap(), [KeyEvent::from(KeyCode::Char('c'))]);
+    }
+}
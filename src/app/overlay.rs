@@ -0,0 +1,217 @@
+//! See [`Overlay`].
+
+use crate::view::{BoxedView, View, ViewWidgetWrapper};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Clear, Widget, WidgetRef},
+};
+
+/// A [`View`] drawn above the active view, ex. a confirm dialog, an error popup or a picker, opened with
+/// [`AppContext::open_overlay()`](super::AppContext::open_overlay).
+///
+/// Unlike views pushed with [`AppContext::push_view()`](super::AppContext::push_view), the view below stays visible
+/// and keeps receiving ticks and messages posted with [`Sender`](super::Sender), but input and actions are delivered
+/// only to the top overlay until it is closed.
+/// Overlay can return a value to its opener with [`AppContext::close_overlay_with()`](super::AppContext::close_overlay_with),
+/// which is delivered as a [`Message`](super::Message) to [`View::handle_message()`] of the view or overlay below.
+///
+/// Overlays belong to the active view which opened them. Pushing, popping or replacing views closes all open overlays
+/// first, without results.
+///
+/// ```
+/// # use crossterm::event::{Event, KeyCode};
+/// # use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+/// # use ratatuio::{app::{App, AppContext, Message, Overlay}, testing::Harness, view::View};
+/// # use std::io;
+/// struct Confirmed(bool);
+///
+/// struct Confirm;
+///
+/// impl View for Confirm {
+///     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+///         "Delete? y/n".render(area, buf);
+///     }
+///
+///     fn handle_events(&mut self, event: &Event, ctx: &AppContext) -> io::Result<()> {
+///         if let Event::Key(key) = event {
+///             match key.code {
+///                 KeyCode::Char('y') => ctx.close_overlay_with(Confirmed(true)),
+///                 KeyCode::Char('n') => ctx.close_overlay_with(Confirmed(false)),
+///                 _ => {}
+///             }
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// struct Files {
+///     count: usize,
+/// }
+///
+/// impl View for Files {
+///     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+///         format!("{} files", self.count).render(area, buf);
+///     }
+///
+///     fn handle_events(&mut self, event: &Event, ctx: &AppContext) -> io::Result<()> {
+///         if let Event::Key(key) = event {
+///             if key.code == KeyCode::Delete {
+///                 ctx.open_overlay(Overlay::new(Confirm).centered(11, 1).dim_background(true));
+///             }
+///         }
+///         Ok(())
+///     }
+///
+///     fn handle_message(&mut self, message: Message, _ctx: &AppContext) -> io::Result<()> {
+///         if let Some(Confirmed(true)) = message.downcast_ref() {
+///             self.count -= 1;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let mut harness = Harness::new(App::new(Files { count: 3 }), 11, 3)?;
+/// harness.key(KeyCode::Delete).run()?;
+/// assert!(harness.active_overlay::<Confirm>().is_some());
+/// assert_eq!(harness.lines(), ["3 files    ", "Delete? y/n", "           "]);
+///
+/// harness.key(KeyCode::Char('y')).run()?;
+/// assert_eq!(harness.lines(), ["2 files    ", "           ", "           "]);
+/// # Ok::<(), ratatuio::Error>(())
+/// ```
+pub struct Overlay {
+    pub(crate) view: BoxedView,
+    dim_background: bool,
+    size: Option<(u16, u16)>,
+}
+
+impl Overlay {
+    /// Creates overlay showing the view over the whole terminal. View is responsible for clearing the area it covers.
+    pub fn new<T: View + Sync + Send + 'static>(view: T) -> Self {
+        Overlay {
+            view: Box::new(view),
+            dim_background: false,
+            size: None,
+        }
+    }
+
+    /// Dims everything drawn below the overlay, so the overlay stands out.
+    pub fn dim_background(mut self, dim: bool) -> Self {
+        self.dim_background = dim;
+        self
+    }
+
+    /// Shows the view in a cleared area of the provided size in the middle of the terminal, ex. for dialogs.
+    /// Size is reduced if the terminal is smaller.
+    pub fn centered(mut self, width: u16, height: u16) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+}
+
+impl WidgetRef for Overlay {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        if self.dim_background {
+            buf.set_style(area, Style::new().add_modifier(Modifier::DIM));
+        }
+
+        let area = match self.size {
+            Some((width, height)) => {
+                let (width, height) = (width.min(area.width), height.min(area.height));
+                let area = Rect::new(
+                    area.x + (area.width - width) / 2,
+                    area.y + (area.height - height) / 2,
+                    width,
+                    height,
+                );
+                Clear.render(area, buf);
+                area
+            }
+            None => area,
+        };
+        ViewWidgetWrapper(self.view.as_view()).render_ref(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::{App, AppContext, Message},
+        testing::Harness,
+    };
+    use crossterm::event::{Event, KeyCode};
+    use std::io;
+
+    struct Menu;
+
+    impl View for Menu {
+        fn render_view(&self, area: Rect, buf: &mut Buffer) {
+            "menu".render(area, buf);
+        }
+
+        fn handle_events(&mut self, _event: &Event, ctx: &AppContext) -> io::Result<()> {
+            ctx.pop_view();
+            Ok(())
+        }
+    }
+
+    struct Page {
+        name: &'static str,
+        count: usize,
+    }
+
+    impl View for Page {
+        fn render_view(&self, area: Rect, buf: &mut Buffer) {
+            format!("{} {}", self.name, self.count).render(area, buf);
+        }
+
+        fn handle_events(&mut self, _event: &Event, ctx: &AppContext) -> io::Result<()> {
+            match self.name {
+                "home" => ctx.push_view(page("details")),
+                _ => ctx.open_overlay(Overlay::new(Menu).centered(4, 1)),
+            }
+            Ok(())
+        }
+
+        fn handle_message(&mut self, message: Message, _ctx: &AppContext) -> io::Result<()> {
+            if let Some(count) = message.downcast_ref::<usize>() {
+                self.count = *count;
+            }
+            Ok(())
+        }
+    }
+
+    fn page(name: &'static str) -> Page {
+        Page { name, count: 0 }
+    }
+
+    #[test]
+    fn overlay_closes_when_opener_is_popped() {
+        let mut harness = Harness::new(App::new(page("home")), 9, 3).unwrap();
+        harness
+            .key(KeyCode::Enter)
+            .key(KeyCode::Enter)
+            .run()
+            .unwrap();
+        assert!(harness.active_overlay::<Menu>().is_some());
+
+        // Menu pops the details page, which opened it.
+        harness.key(KeyCode::Enter).run().unwrap();
+        assert!(harness.active_overlay::<Menu>().is_none());
+        assert_eq!(harness.lines(), ["home 0   ", "         ", "         "]);
+    }
+
+    #[test]
+    fn sender_messages_reach_view_below_overlay() {
+        let mut harness = Harness::new(App::new(page("details")), 9, 3).unwrap();
+        harness.key(KeyCode::Enter).run().unwrap();
+        assert!(harness.active_overlay::<Menu>().is_some());
+
+        harness.context().sender().send(5_usize).unwrap();
+        harness.run().unwrap();
+        assert_eq!(harness.lines(), ["details 5", "  menu   ", "         "]);
+    }
+}
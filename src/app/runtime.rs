@@ -0,0 +1,535 @@
+//! Phases of the application loop shared by [`App::run()`] and its async counterpart.
+
+use super::{
+    crash::TerminalGuard,
+    help::Help,
+    inbox::{InputReader, Signal},
+    middleware::is_pressed,
+    App, Flow,
+};
+use crate::{
+    backend::TerminalBackend,
+    cast::CastRecorder,
+    export,
+    keymap::{Action, KeyMatcher, Keymap},
+    recording::Recorder,
+    view::ViewWidgetWrapper,
+    Error,
+};
+use crossterm::event::Event;
+use ratatui::{
+    backend::Backend,
+    buffer::Buffer,
+    widgets::{Paragraph, Widget, WidgetRef},
+    Terminal, TerminalOptions, Viewport,
+};
+use std::{
+    any::Any,
+    fs::File,
+    io::{self, BufWriter},
+    time::{Duration, Instant},
+};
+
+/// Tracks when the view was last ticked and drawn, and whether it has to be redrawn.
+pub(crate) struct Schedule {
+    last_tick: Instant,
+    last_frame: Option<Instant>,
+    dirty: bool,
+}
+
+impl Schedule {
+    pub(crate) fn new() -> Self {
+        Schedule {
+            last_tick: Instant::now(),
+            last_frame: None,
+            dirty: true,
+        }
+    }
+
+    /// Marks the view to be redrawn with the next frame.
+    pub(crate) fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+}
+
+/// Backend set with [`App::with_backend()`] with its concrete type erased, so [`App`] does not have to be generic.
+pub(crate) trait BackendLoop: Send {
+    fn run(self: Box<Self>, app: &mut App) -> Result<(), Error>;
+
+    #[cfg(feature = "tokio")]
+    fn run_async<'a>(
+        self: Box<Self>,
+        app: &'a mut App,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), Error>> + 'a>>;
+}
+
+impl<B: TerminalBackend + Send + 'static> BackendLoop for B {
+    fn run(self: Box<Self>, app: &mut App) -> Result<(), Error> {
+        app.run_on(*self)
+    }
+
+    #[cfg(feature = "tokio")]
+    fn run_async<'a>(
+        self: Box<Self>,
+        app: &'a mut App,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), Error>> + 'a>> {
+        Box::pin(app.run_on_async(*self))
+    }
+}
+
+impl App {
+    pub(super) fn run_loop(mut self) -> Result<Option<Box<dyn Any + Send>>, Error> {
+        let backend = self.take_backend();
+        backend.run(&mut self)?;
+        Ok(self.context.take_exit_value())
+    }
+
+    fn run_on<B: TerminalBackend>(&mut self, mut backend: B) -> Result<(), Error> {
+        let config = self.terminal_config();
+        let guard = TerminalGuard::enter(
+            &mut backend,
+            &config,
+            self.crash_handler.clone(),
+            self.context.inbox().clone(),
+        )?;
+        let result = self.event_loop(backend);
+        self.context.inbox().close();
+        guard.exit(&result);
+        result
+    }
+
+    fn event_loop<B: TerminalBackend>(&mut self, backend: B) -> Result<(), Error> {
+        let reads_events = backend.reads_events();
+        let mut terminal = self.create_terminal(backend)?;
+        self.start_recording(&terminal)?;
+        let _input = InputReader::spawn(
+            self.context.inbox().clone(),
+            self.replay.take(),
+            reads_events,
+        );
+        let mut schedule = Schedule::new();
+        self.enter()?;
+
+        while self.context.is_running() {
+            self.draw(&mut terminal, &mut schedule)?;
+
+            let context = self.context.clone();
+            for signal in context.inbox().wait(self.timeout(&schedule)) {
+                if !self.prepare_dispatch(&mut schedule)? {
+                    break;
+                }
+                self.dispatch(signal, &mut schedule)?;
+            }
+
+            let events = self.expire_keys(Instant::now(), &mut schedule)?;
+            self.deliver(events)?;
+            self.expire_toasts(Instant::now(), &mut schedule);
+            self.tick(&mut schedule)?;
+        }
+
+        self.draw_last_frame(&mut terminal, &mut schedule)?;
+        self.exit()
+    }
+
+    pub(super) fn create_terminal<B: Backend>(&self, backend: B) -> Result<Terminal<B>, Error> {
+        let options = TerminalOptions {
+            viewport: self.viewport.clone(),
+        };
+        Ok(Terminal::with_options(backend, options)?)
+    }
+
+    /// Draws the final state of the view in inline or fixed viewport, so it stays in the scrollback, and moves the
+    /// cursor to the row below the viewport, scrolling the terminal if the viewport ends at its last row, so output
+    /// printed after the application exits does not overwrite the frame.
+    pub(super) fn draw_last_frame<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        schedule: &mut Schedule,
+    ) -> Result<(), Error> {
+        if self.viewport != Viewport::Fullscreen {
+            schedule.last_frame = None;
+            schedule.invalidate();
+            self.draw(terminal, schedule)?;
+
+            let area = terminal.get_frame().area();
+            let last_row = terminal.size()?.height.saturating_sub(1);
+            if area.bottom() > last_row {
+                terminal.set_cursor_position((0, last_row))?;
+                terminal.backend_mut().append_lines(1)?;
+            }
+            terminal.set_cursor_position((0, area.bottom().min(last_row)))?;
+        }
+        Ok(())
+    }
+
+    /// Creates the file set with [`App::record_to()`] and writes the current terminal size into it.
+    pub(crate) fn start_recording<B: Backend>(
+        &mut self,
+        terminal: &Terminal<B>,
+    ) -> Result<(), Error> {
+        if let Some(path) = &self.record_path {
+            let size = terminal.size()?;
+            self.recorder = Some(Recorder::create(path, (size.width, size.height))?);
+        }
+        Ok(())
+    }
+
+    /// Writes the event into the recording, if it was requested with [`App::record_to()`].
+    fn record(&mut self, event: &Event) -> Result<(), Error> {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(event)?;
+        }
+        Ok(())
+    }
+
+    /// Records the event, then passes it through the key set with [`App::screenshot_key()`], the middlewares, the key
+    /// set with [`App::dismiss_toast_key()`], the help overlay and the keymap, whose actions are delivered to the view
+    /// right away.
+    ///
+    /// Returns events to deliver to the view, which are empty if the event was consumed. Digits typed as a count of the
+    /// keymap, which no binding followed, are delivered before the event.
+    pub(crate) fn filter_event(
+        &mut self,
+        event: Event,
+        schedule: &mut Schedule,
+    ) -> Result<Vec<Event>, Error> {
+        self.record(&event)?;
+
+        if let Some((key, _)) = &self.screenshot_key {
+            if is_pressed(&event, key) {
+                self.screenshot_requested = true;
+                schedule.invalidate();
+                return Ok(Vec::new());
+            }
+        }
+
+        let context = self.context.clone();
+        let mut event = event;
+        for middleware in &mut self.middlewares {
+            match middleware.handle(event, &context)? {
+                Flow::Pass(next) => event = next,
+                Flow::Consume => {
+                    schedule.invalidate();
+                    return Ok(Vec::new());
+                }
+            }
+        }
+
+        if let Some(key) = &self.toasts.dismiss_key {
+            if is_pressed(&event, key) && self.toasts.dismiss() {
+                schedule.invalidate();
+                return Ok(Vec::new());
+            }
+        }
+
+        if self.filter_help(&event) {
+            schedule.invalidate();
+            return Ok(Vec::new());
+        }
+
+        if let Event::Key(key) = &event {
+            let scopes = self.keymap_scopes()?;
+            let matched = self
+                .key_matcher
+                .feed(&self.keymap, &scopes, key, Instant::now());
+            self.handle_actions(matched.actions, schedule)?;
+            let mut events: Vec<Event> = matched.unmatched.into_iter().map(Event::Key).collect();
+            if !matched.consumed {
+                events.push(event);
+            }
+            return Ok(events);
+        }
+
+        Ok(vec![event])
+    }
+
+    /// Opens or closes the help overlay set with [`App::help_key()`] and passes input to it while it is open.
+    ///
+    /// Returns `true` if the event was consumed by the overlay.
+    fn filter_help(&mut self, event: &Event) -> bool {
+        let toggled = self.help_key.is_some_and(|key| is_pressed(event, &key));
+        match &mut self.help {
+            Some(_) if toggled => self.help = None,
+            Some(help) => {
+                if matches!(
+                    event,
+                    Event::Resize(..) | Event::FocusGained | Event::FocusLost
+                ) {
+                    return false;
+                }
+                if !help.handle_event(event) {
+                    self.help = None;
+                }
+            }
+            None if toggled => {
+                self.help = Some(Help::default());
+                self.key_matcher = KeyMatcher::default();
+                self.context.set_pending_keys(None);
+            }
+            None => return false,
+        }
+        true
+    }
+
+    /// Returns keymap contexts of the top overlay, or of the active view in its current mode.
+    pub(super) fn keymap_scopes(&self) -> Result<Vec<String>, Error> {
+        if let Some(overlay) = self.overlays.last() {
+            return Ok(Keymap::scopes(overlay.view.keymap_context(), None));
+        }
+        let mode = self.modes.last().cloned().flatten();
+        Ok(Keymap::scopes(
+            self.view()?.keymap_context(),
+            mode.as_deref(),
+        ))
+    }
+
+    /// Delivers actions of the keymap set with [`App::keymap()`] to the view and updates pending keys shown by
+    /// [`AppContext::pending_keys()`](super::AppContext::pending_keys).
+    fn handle_actions(
+        &mut self,
+        actions: Vec<Action>,
+        schedule: &mut Schedule,
+    ) -> Result<(), Error> {
+        let context = self.context.clone();
+        context.set_pending_keys(self.key_matcher.pending());
+        for action in actions {
+            self.focused_mut()?.handle_action(&action, &context)?;
+        }
+        schedule.invalidate();
+        Ok(())
+    }
+
+    /// Triggers action of the pending key sequence if it timed out, see [`Keymap::sequence_timeout()`](crate::keymap::Keymap::sequence_timeout).
+    ///
+    /// Returns keys of the count and the sequence to deliver to the view, if no binding matched them.
+    pub(crate) fn expire_keys(
+        &mut self,
+        now: Instant,
+        schedule: &mut Schedule,
+    ) -> Result<Vec<Event>, Error> {
+        if self
+            .key_matcher
+            .deadline()
+            .filter(|deadline| *deadline <= now)
+            .is_none()
+        {
+            return Ok(Vec::new());
+        }
+        let scopes = self.keymap_scopes()?;
+        let matched = self.key_matcher.expire(&self.keymap, &scopes, now);
+        self.handle_actions(matched.actions, schedule)?;
+        Ok(matched.unmatched.into_iter().map(Event::Key).collect())
+    }
+
+    /// Delivers the events to the top overlay or the current view.
+    pub(crate) fn deliver(&mut self, events: Vec<Event>) -> Result<(), Error> {
+        let context = self.context.clone();
+        for event in events {
+            self.focused_mut()?.handle_events(&event, &context)?;
+        }
+        Ok(())
+    }
+
+    /// Hides toasts whose timeout set with [`App::toast_timeout()`] passed.
+    pub(crate) fn expire_toasts(&mut self, now: Instant, schedule: &mut Schedule) {
+        if self.toasts.expire(now) {
+            schedule.invalidate();
+        }
+    }
+
+    /// Applies queued navigation and draws the current view if it changed and the frame rate allows it.
+    pub(crate) fn draw<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        schedule: &mut Schedule,
+    ) -> Result<(), Error> {
+        schedule.dirty |= self.apply_navigation()?;
+
+        for text in self.context.take_inserted() {
+            let height = u16::try_from(text.height()).unwrap_or(u16::MAX);
+            terminal.insert_before(height, |buf| Paragraph::new(text).render(buf.area, buf))?;
+            schedule.invalidate();
+        }
+
+        let now = Instant::now();
+        for (level, message) in self.context.take_notifications() {
+            self.toasts.show(level, message, now);
+            schedule.invalidate();
+        }
+
+        let next_frame = schedule
+            .last_frame
+            .and_then(|last_frame| last_frame.checked_add(self.frame_interval));
+        if schedule.dirty && next_frame.filter(|next_frame| now < *next_frame).is_none() {
+            let scopes = self.keymap_scopes()?;
+            let help = self
+                .help
+                .as_mut()
+                .map(|help| help.widget(&self.keymap, &scopes));
+            let view = self.view()?;
+            let overlays = &self.overlays;
+            let toasts = &self.toasts;
+            let frame = terminal.draw(|frame: &mut ratatui::Frame<'_>| {
+                ViewWidgetWrapper(view.as_view()).render_ref(frame.area(), frame.buffer_mut());
+                for overlay in overlays {
+                    overlay.render_ref(frame.area(), frame.buffer_mut());
+                }
+                if let Some(help) = &help {
+                    help.render(frame.area(), frame.buffer_mut());
+                }
+                toasts.render(frame.area(), frame.buffer_mut());
+            })?;
+            self.cast_frame(frame.buffer)?;
+            if std::mem::take(&mut self.screenshot_requested) {
+                if let Some((_, path)) = &self.screenshot_key {
+                    export::save(frame.buffer, path)?;
+                }
+            }
+            schedule.last_frame = Some(now);
+            schedule.dirty = false;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the drawn frame into the cast, if it was requested with [`App::cast_to()`].
+    fn cast_frame(&mut self, buffer: &Buffer) -> Result<(), Error> {
+        if let (None, Some(path)) = (&self.cast, &self.cast_path) {
+            self.cast = Some(CastRecorder::new(BufWriter::new(File::create(path)?)));
+        }
+        if let Some(cast) = &mut self.cast {
+            cast.frame(buffer)?;
+        }
+        Ok(())
+    }
+
+    /// Returns how long the loop can wait for signals before it has to tick or draw, or `None` if it can wait indefinitely.
+    pub(crate) fn timeout(&self, schedule: &Schedule) -> Option<Duration> {
+        let next_tick = self
+            .tick_rate
+            .and_then(|tick_rate| schedule.last_tick.checked_add(tick_rate));
+        let next_frame = schedule
+            .last_frame
+            .filter(|_| schedule.dirty)
+            .and_then(|last_frame| last_frame.checked_add(self.frame_interval));
+
+        [
+            next_tick,
+            next_frame,
+            self.key_matcher.deadline(),
+            self.toasts.deadline(),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+        .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Applies navigation requested by previous signal, so each signal is delivered to the view active at that moment.
+    ///
+    /// Returns `false` if application was requested to quit and remaining signals should be dropped.
+    pub(crate) fn prepare_dispatch(&mut self, schedule: &mut Schedule) -> Result<bool, Error> {
+        schedule.dirty |= self.apply_navigation()?;
+        Ok(self.context.is_running())
+    }
+
+    /// Delivers input to the top overlay or the current view, and messages to the current view.
+    pub(crate) fn dispatch(
+        &mut self,
+        signal: Signal,
+        schedule: &mut Schedule,
+    ) -> Result<(), Error> {
+        let context = self.context.clone();
+        match signal {
+            Signal::Input(event) => {
+                let events = self.filter_event(event, schedule)?;
+                self.deliver(events)?;
+            }
+            Signal::InputError(err) => return Err(err.into()),
+            Signal::Message(message) => self.view_mut()?.handle_message(message, &context)?,
+            Signal::Wake => {}
+            Signal::Crashed => {
+                return Err(io::Error::other("terminal was restored after a panic").into())
+            }
+        }
+
+        schedule.invalidate();
+        Ok(())
+    }
+
+    /// Calls [`View::on_tick()`](crate::view::View::on_tick) if tick rate was set and enough time passed since the previous tick.
+    pub(crate) fn tick(&mut self, schedule: &mut Schedule) -> Result<(), Error> {
+        if let Some(tick_rate) = self.tick_rate {
+            let elapsed = schedule.last_tick.elapsed();
+            if elapsed >= tick_rate {
+                schedule.last_tick = Instant::now();
+                self.tick_view(elapsed, schedule)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Calls [`View::on_tick()`](crate::view::View::on_tick) of the current view and of all overlays above it.
+    pub(crate) fn tick_view(
+        &mut self,
+        elapsed: Duration,
+        schedule: &mut Schedule,
+    ) -> Result<(), Error> {
+        let context = self.context.clone();
+        self.view_mut()?.on_tick(elapsed, &context)?;
+        for overlay in &mut self.overlays {
+            overlay.view.on_tick(elapsed, &context)?;
+        }
+        schedule.invalidate();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, layout::Rect};
+
+    struct Page;
+
+    impl crate::view::View for Page {
+        fn render_view(&self, area: Rect, buf: &mut Buffer) {
+            Paragraph::new("last").render(area, buf);
+        }
+    }
+
+    fn last_frame_cursor(backend: TestBackend, viewport: Viewport) -> (u16, u16) {
+        let mut app = App::new(Page).viewport(viewport);
+        let mut terminal = app.create_terminal(backend).unwrap();
+        let mut schedule = Schedule::new();
+        app.enter().unwrap();
+        app.draw(&mut terminal, &mut schedule).unwrap();
+        app.draw_last_frame(&mut terminal, &mut schedule).unwrap();
+        let position = terminal.get_cursor_position().unwrap();
+        (position.x, position.y)
+    }
+
+    #[test]
+    fn last_frame_leaves_cursor_below_inline_viewport() {
+        let mut backend = TestBackend::new(8, 10);
+        backend.set_cursor_position((0, 2)).unwrap();
+
+        assert_eq!(last_frame_cursor(backend, Viewport::Inline(3)), (0, 5));
+    }
+
+    #[test]
+    fn last_frame_scrolls_when_inline_viewport_ends_at_last_row() {
+        let mut backend = TestBackend::new(8, 5);
+        backend.set_cursor_position((0, 4)).unwrap();
+
+        assert_eq!(last_frame_cursor(backend, Viewport::Inline(3)), (0, 4));
+    }
+
+    #[test]
+    fn last_frame_leaves_cursor_below_fixed_viewport() {
+        let viewport = Viewport::Fixed(Rect::new(2, 1, 4, 2));
+
+        assert_eq!(last_frame_cursor(TestBackend::new(8, 5), viewport), (0, 3));
+    }
+}
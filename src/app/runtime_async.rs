@@ -0,0 +1,178 @@
+//! Async counterpart of the application loop, available with `tokio` feature.
+
+use super::{
+    crash::TerminalGuard,
+    inbox::{InputReader, Signal},
+    runtime::Schedule,
+    App,
+};
+use crate::{backend::TerminalBackend, Error};
+use crossterm::event::{Event, EventStream};
+use futures::StreamExt;
+use std::{any::Any, future, io, time::Instant};
+
+impl App {
+    pub(super) async fn run_loop_async(mut self) -> Result<Option<Box<dyn Any + Send>>, Error> {
+        let backend = self.take_backend();
+        backend.run_async(&mut self).await?;
+        Ok(self.context.take_exit_value())
+    }
+
+    pub(super) async fn run_on_async<B: TerminalBackend>(
+        &mut self,
+        mut backend: B,
+    ) -> Result<(), Error> {
+        let config = self.terminal_config();
+        let guard = TerminalGuard::enter(
+            &mut backend,
+            &config,
+            self.crash_handler.clone(),
+            self.context.inbox().clone(),
+        )?;
+        let result = self.event_loop_async(backend).await;
+        self.context.inbox().close();
+        guard.exit(&result);
+        result
+    }
+
+    async fn event_loop_async<B: TerminalBackend>(&mut self, backend: B) -> Result<(), Error> {
+        let reads_events = backend.reads_events();
+        let mut terminal = self.create_terminal(backend)?;
+        self.start_recording(&terminal)?;
+        let inbox = self.context.inbox().clone();
+        // Replayed events have to come before terminal input, so the whole input is then read by the reader thread.
+        let (mut events, _input) = match self.replay.take() {
+            Some(recording) => (
+                None,
+                Some(InputReader::spawn(
+                    inbox.clone(),
+                    Some(recording),
+                    reads_events,
+                )),
+            ),
+            None => (reads_events.then(EventStream::new), None),
+        };
+        let mut schedule = Schedule::new();
+        self.enter()?;
+
+        while self.context.is_running() {
+            self.draw(&mut terminal, &mut schedule)?;
+
+            let timeout = self.timeout(&schedule);
+            let sleep = async {
+                match timeout {
+                    Some(timeout) => tokio::time::sleep(timeout).await,
+                    None => future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                event = next_event(&mut events) => match event {
+                    Some(Ok(event)) => {
+                        if self.prepare_dispatch(&mut schedule)? {
+                            self.dispatch_async(event, &mut schedule).await?;
+                        }
+                    }
+                    Some(Err(err)) => return Err(err.into()),
+                    None => self.context.quit(),
+                },
+                signals = inbox.wait_async() => {
+                    for signal in signals {
+                        if !self.prepare_dispatch(&mut schedule)? {
+                            break;
+                        }
+                        match signal {
+                            Signal::Input(event) => self.dispatch_async(event, &mut schedule).await?,
+                            signal => self.dispatch(signal, &mut schedule)?,
+                        }
+                    }
+                },
+                _ = sleep => {}
+            }
+
+            let events = self.expire_keys(Instant::now(), &mut schedule)?;
+            self.deliver_async(events, &mut schedule).await?;
+            self.expire_toasts(Instant::now(), &mut schedule);
+            self.tick(&mut schedule)?;
+        }
+
+        self.draw_last_frame(&mut terminal, &mut schedule)?;
+        self.exit()
+    }
+
+    /// Delivers the event to [`View::handle_events_async()`](crate::view::View::handle_events_async) of the top overlay or
+    /// the current view.
+    async fn dispatch_async(&mut self, event: Event, schedule: &mut Schedule) -> Result<(), Error> {
+        let events = self.filter_event(event, schedule)?;
+        self.deliver_async(events, schedule).await
+    }
+
+    /// Delivers the events to [`View::handle_events_async()`](crate::view::View::handle_events_async) of the top
+    /// overlay or the current view.
+    async fn deliver_async(
+        &mut self,
+        events: Vec<Event>,
+        schedule: &mut Schedule,
+    ) -> Result<(), Error> {
+        let context = self.context.clone();
+        for event in events {
+            self.focused_mut()?
+                .handle_events_async(&event, &context)
+                .await?;
+            schedule.invalidate();
+        }
+        Ok(())
+    }
+}
+
+/// Reads the next event from the stream, or waits forever if terminal events are read by [`InputReader`].
+async fn next_event(events: &mut Option<EventStream>) -> Option<io::Result<Event>> {
+    match events {
+        Some(events) => events.next().await,
+        None => future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{app::AppContext, recording::Recording, view::View};
+    use crossterm::event::{Event, KeyCode};
+    use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect};
+    use std::time::Duration;
+
+    struct Counter(usize);
+
+    impl View for Counter {
+        fn render_view(&self, _area: Rect, _buf: &mut Buffer) {}
+
+        fn handle_events_async<'a>(
+            &'a mut self,
+            event: &'a Event,
+            ctx: &'a AppContext,
+        ) -> crate::view::ViewFuture<'a> {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                match event {
+                    Event::Key(key) if key.code == KeyCode::Char('q') => ctx.quit_with(self.0),
+                    Event::Key(_) => self.0 += 1,
+                    _ => {}
+                }
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn run_async_exits_with_quit_value() {
+        let mut recording = Recording::new();
+        for code in [KeyCode::Char('a'), KeyCode::Char('b'), KeyCode::Char('q')] {
+            recording.push(Duration::ZERO, Event::Key(code.into()));
+        }
+        let app = super::App::new(Counter(0))
+            .with_backend(TestBackend::new(10, 2))
+            .replay(recording);
+
+        let exit_value = app.run_async_with_result::<usize>().await;
+        assert_eq!(exit_value.unwrap(), Some(2));
+    }
+}
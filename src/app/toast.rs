@@ -0,0 +1,181 @@
+//! Toast notifications drawn above everything else, see [`AppContext::notify()`](super::AppContext::notify).
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::Text,
+    widgets::{Block, BorderType, Clear, Padding, Paragraph, Widget},
+};
+use std::time::{Duration, Instant};
+
+/// Widest toast, longer lines are cut.
+const MAX_WIDTH: u16 = 50;
+
+/// Severity of a toast notification, which decides its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Level {
+    /// Blue, ex. a finished background task.
+    Info,
+    /// Green, ex. a saved file.
+    Success,
+    /// Yellow, ex. a skipped item.
+    Warning,
+    /// Red, ex. a failed request.
+    Error,
+}
+
+impl Level {
+    fn color(self) -> Color {
+        match self {
+            Level::Info => Color::Blue,
+            Level::Success => Color::Green,
+            Level::Warning => Color::Yellow,
+            Level::Error => Color::Red,
+        }
+    }
+}
+
+/// Corner of the terminal where toasts are stacked, see [`App::toast_corner()`](super::App::toast_corner).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Corner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Notification shown until its deadline.
+struct Toast {
+    level: Level,
+    message: String,
+    /// `None` if the timeout is too long to be represented, the toast is then shown until dismissed.
+    deadline: Option<Instant>,
+}
+
+/// Visible toasts and their configuration.
+pub(crate) struct Toasts {
+    pub(crate) corner: Corner,
+    /// How long every toast stays visible.
+    pub(crate) timeout: Duration,
+    pub(crate) dismiss_key: Option<KeyEvent>,
+    /// Toasts in order they were shown, the newest is the last.
+    visible: Vec<Toast>,
+}
+
+impl Default for Toasts {
+    fn default() -> Self {
+        Toasts {
+            corner: Corner::default(),
+            timeout: Duration::from_secs(5),
+            dismiss_key: None,
+            visible: Vec::new(),
+        }
+    }
+}
+
+impl Toasts {
+    /// Shows the toast until the timeout passes from `now`.
+    pub(crate) fn show(&mut self, level: Level, message: String, now: Instant) {
+        self.visible.push(Toast {
+            level,
+            message,
+            deadline: now.checked_add(self.timeout),
+        });
+    }
+
+    /// Hides the newest toast. Returns `false` if no toast was visible.
+    pub(crate) fn dismiss(&mut self) -> bool {
+        self.visible.pop().is_some()
+    }
+
+    /// Hides toasts whose deadline passed. Returns `true` if any toast was hidden.
+    pub(crate) fn expire(&mut self, now: Instant) -> bool {
+        let count = self.visible.len();
+        self.visible
+            .retain(|toast| toast.deadline.filter(|deadline| *deadline <= now).is_none());
+        self.visible.len() != count
+    }
+
+    /// Returns when the last toast should be hidden, or `None` if no toast has a deadline.
+    pub(crate) fn last_deadline(&self) -> Option<Instant> {
+        self.visible.iter().filter_map(|toast| toast.deadline).max()
+    }
+
+    /// Returns when the next toast should be hidden, or `None` if no visible toast has a deadline.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.visible.iter().filter_map(|toast| toast.deadline).min()
+    }
+}
+
+impl Widget for &Toasts {
+    /// Stacks toasts from the corner, the newest nearest to it. Toasts which do not fit are not drawn.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let top = matches!(self.corner, Corner::TopLeft | Corner::TopRight);
+        let left = matches!(self.corner, Corner::TopLeft | Corner::BottomLeft);
+        let mut free = area;
+
+        for toast in self.visible.iter().rev() {
+            let text = Text::from(toast.message.as_str());
+            let width = u16::try_from(text.width())
+                .unwrap_or(u16::MAX)
+                .saturating_add(4)
+                .min(MAX_WIDTH)
+                .min(free.width);
+            let height = u16::try_from(text.height())
+                .unwrap_or(u16::MAX)
+                .saturating_add(2);
+            if height > free.height {
+                break;
+            }
+
+            let x = if left { free.x } else { free.right() - width };
+            let y = if top { free.y } else { free.bottom() - height };
+            let toast_area = Rect::new(x, y, width, height);
+            free.height -= height;
+            if top {
+                free.y += height;
+            }
+
+            let block = Block::bordered()
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().fg(toast.level.color()))
+                .padding(Padding::horizontal(1));
+            Clear.render(toast_area, buf);
+            Paragraph::new(text).block(block).render(toast_area, buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toast_is_hidden_after_timeout() {
+        let (mut toasts, now) = (Toasts::default(), Instant::now());
+        toasts.show(Level::Info, "saved".to_owned(), now);
+
+        assert_eq!(toasts.deadline(), Some(now + toasts.timeout));
+        assert!(!toasts.expire(now));
+        assert!(toasts.expire(now + toasts.timeout));
+        assert_eq!(toasts.deadline(), None);
+    }
+
+    #[test]
+    fn overflowing_timeout_shows_toast_until_dismissed() {
+        let mut toasts = Toasts {
+            timeout: Duration::MAX,
+            ..Toasts::default()
+        };
+        let now = Instant::now();
+        toasts.show(Level::Info, "saved".to_owned(), now);
+
+        assert_eq!(toasts.deadline(), None);
+        assert_eq!(toasts.last_deadline(), None);
+        assert!(!toasts.expire(now + Duration::from_secs(3600)));
+        assert!(toasts.dismiss());
+    }
+}
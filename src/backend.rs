@@ -0,0 +1,256 @@
+//! This module contains [`TerminalBackend`] trait, which allows to run application on any ratatui [`Backend`] set with
+//! [`App::with_backend()`](crate::app::App::with_backend).
+//!
+//! By default application draws with crossterm on stdout. Drawing on stderr keeps stdout free for the output of the
+//! application, ex. for a picker whose result is piped to another program:
+//!
+//! ```no_run
+//! # use ratatui::{backend::CrosstermBackend, buffer::Buffer, layout::Rect};
+//! # use ratatuio::{app::App, view::View};
+//! # use std::io;
+//! # struct Picker;
+//! # impl View for Picker {
+//! #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+//! # }
+//! let chosen: Option<String> = App::new(Picker)
+//!     .with_backend(CrosstermBackend::new(io::stderr()))
+//!     .run_with_result()?;
+//!
+//! if let Some(chosen) = chosen {
+//!     println!("{chosen}");
+//! }
+//! # Ok::<(), ratatuio::Error>(())
+//! ```
+//!
+//! Implementations are provided for:
+//! - `CrosstermBackend<Stdout>` and `CrosstermBackend<Stderr>`, which enter raw mode and alternate screen on the
+//!   stream they draw on.
+//! - `TermionBackend` with `termion` feature and `TermwizBackend` with `termwiz` feature. These backends configure the
+//!   terminal when they are created and restore it when dropped, so their setup and restorer do nothing. With them:
+//!   - Terminal events are read with crossterm like with the default backend. Input read by the backend itself, ex.
+//!     with termion's `TermRead`, is not delivered to views.
+//!   - [`App::viewport()`](crate::app::App::viewport) sets only the area the application draws in. Raw mode and
+//!     alternate screen stay as the backend was created, ex. `TermwizBackend::new()` always draws on the alternate
+//!     screen, so inline viewports need a backend created without it.
+//!   - Input features [`App::mouse_capture()`](crate::app::App::mouse_capture),
+//!     [`App::bracketed_paste()`](crate::app::App::bracketed_paste),
+//!     [`App::focus_change()`](crate::app::App::focus_change) and
+//!     [`App::keyboard_enhancement()`](crate::app::App::keyboard_enhancement) cannot be enabled, requesting them
+//!     fails with [`io::ErrorKind::Unsupported`].
+//! - [`TestBackend`], which does not touch the terminal and does not read terminal events, so it accepts any features.
+
+use crossterm::{
+    cursor,
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+use ratatui::backend::{Backend, CrosstermBackend, TestBackend};
+use std::{
+    io::{self, Stderr, Stdout, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Whether keyboard enhancement flags were pushed by [`setup_crossterm()`]. Stdout and stderr share the terminal, so
+/// the flags are tracked for the whole terminal rather than per backend.
+static KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+
+/// Terminal features requested by the application, passed to [`TerminalBackend::setup()`] and
+/// [`TerminalBackend::restorer()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalConfig {
+    /// Draw on the alternate screen, `false` if application runs in an inline or fixed
+    /// [`Viewport`](ratatui::Viewport) set with [`App::viewport()`](crate::app::App::viewport).
+    pub alternate_screen: bool,
+    /// Report mouse events, see [`App::mouse_capture()`](crate::app::App::mouse_capture).
+    pub mouse_capture: bool,
+    /// Report pasted text as a single event, see [`App::bracketed_paste()`](crate::app::App::bracketed_paste).
+    pub bracketed_paste: bool,
+    /// Report focus changes, see [`App::focus_change()`](crate::app::App::focus_change).
+    pub focus_change: bool,
+    /// Enhanced keyboard reporting, see [`App::keyboard_enhancement()`](crate::app::App::keyboard_enhancement).
+    pub keyboard_enhancement: Option<KeyboardEnhancementFlags>,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        TerminalConfig {
+            alternate_screen: true,
+            mouse_capture: false,
+            bracketed_paste: false,
+            focus_change: false,
+            keyboard_enhancement: None,
+        }
+    }
+}
+
+/// Function restoring the terminal, see [`TerminalBackend::restorer()`].
+pub type Restorer = Arc<dyn Fn() -> io::Result<()> + Send + Sync>;
+
+/// A ratatui [`Backend`] which can prepare the terminal for the application and restore it afterwards.
+pub trait TerminalBackend: Backend {
+    /// Prepares the terminal before the first frame is drawn, ex. enters raw mode and alternate screen.
+    fn setup(&mut self, config: &TerminalConfig) -> io::Result<()>;
+
+    /// Returns function undoing [`TerminalBackend::setup()`].
+    ///
+    /// Restorer is called when the application loop exits and from the panic hook while the backend may be borrowed,
+    /// so it cannot borrow the backend itself.
+    fn restorer(&self, config: &TerminalConfig) -> Restorer;
+
+    /// Returns `true` if terminal events should be read with crossterm and passed to views. Defaults to `true`.
+    fn reads_events(&self) -> bool {
+        true
+    }
+}
+
+impl TerminalBackend for CrosstermBackend<Stdout> {
+    fn setup(&mut self, config: &TerminalConfig) -> io::Result<()> {
+        setup_crossterm(self, config)
+    }
+
+    fn restorer(&self, config: &TerminalConfig) -> Restorer {
+        let config = config.clone();
+        Arc::new(move || restore_crossterm(&mut io::stdout(), &config))
+    }
+}
+
+impl TerminalBackend for CrosstermBackend<Stderr> {
+    fn setup(&mut self, config: &TerminalConfig) -> io::Result<()> {
+        setup_crossterm(self, config)
+    }
+
+    fn restorer(&self, config: &TerminalConfig) -> Restorer {
+        let config = config.clone();
+        Arc::new(move || restore_crossterm(&mut io::stderr(), &config))
+    }
+}
+
+fn setup_crossterm(writer: &mut impl Write, config: &TerminalConfig) -> io::Result<()> {
+    enable_raw_mode()?;
+    if config.alternate_screen {
+        execute!(writer, EnterAlternateScreen)?;
+    }
+    if config.mouse_capture {
+        execute!(writer, EnableMouseCapture)?;
+    }
+    if config.bracketed_paste {
+        execute!(writer, EnableBracketedPaste)?;
+    }
+    if config.focus_change {
+        execute!(writer, EnableFocusChange)?;
+    }
+    // Crossterm fails to push the flags where they are not supported, ex. on Windows.
+    if let Some(flags) = config.keyboard_enhancement {
+        if supports_keyboard_enhancement().unwrap_or(false) {
+            execute!(writer, PushKeyboardEnhancementFlags(flags))?;
+            KEYBOARD_ENHANCED.store(true, Ordering::SeqCst);
+        }
+    }
+    Ok(())
+}
+
+/// Undoes [`setup_crossterm()`] in reverse order. Every step is attempted even if previous one failed, because
+/// setup could have failed in the middle, and the first error is returned.
+fn restore_crossterm(writer: &mut impl Write, config: &TerminalConfig) -> io::Result<()> {
+    let mut results = Vec::new();
+    if KEYBOARD_ENHANCED.swap(false, Ordering::SeqCst) {
+        results.push(execute!(writer, PopKeyboardEnhancementFlags));
+    }
+    if config.focus_change {
+        results.push(execute!(writer, DisableFocusChange));
+    }
+    if config.bracketed_paste {
+        results.push(execute!(writer, DisableBracketedPaste));
+    }
+    if config.mouse_capture {
+        results.push(execute!(writer, DisableMouseCapture));
+    }
+    results.push(disable_raw_mode());
+    results.push(restore_screen(writer, config));
+    results.into_iter().collect()
+}
+
+fn restore_screen(writer: &mut impl Write, config: &TerminalConfig) -> io::Result<()> {
+    if config.alternate_screen {
+        execute!(writer, LeaveAlternateScreen, cursor::Show)
+    } else {
+        // Application loop already moved the cursor below the last frame.
+        execute!(writer, cursor::Show)
+    }
+}
+
+/// Test backend has no terminal, so requested features are accepted and have no effect.
+impl TerminalBackend for TestBackend {
+    fn setup(&mut self, _config: &TerminalConfig) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn restorer(&self, _config: &TerminalConfig) -> Restorer {
+        Arc::new(|| Ok(()))
+    }
+
+    fn reads_events(&self) -> bool {
+        false
+    }
+}
+
+/// Termion backend is prepared by the writer it was created with, see [module documentation](self) for supported
+/// options.
+#[cfg(all(not(windows), feature = "termion"))]
+impl<W: Write> TerminalBackend for ratatui::backend::TermionBackend<W> {
+    fn setup(&mut self, config: &TerminalConfig) -> io::Result<()> {
+        reject_input_features("termion", config)
+    }
+
+    fn restorer(&self, _config: &TerminalConfig) -> Restorer {
+        Arc::new(|| Ok(()))
+    }
+}
+
+/// Termwiz backend is prepared when it is created, see [module documentation](self) for supported options.
+#[cfg(feature = "termwiz")]
+impl TerminalBackend for ratatui::backend::TermwizBackend {
+    fn setup(&mut self, config: &TerminalConfig) -> io::Result<()> {
+        reject_input_features("termwiz", config)
+    }
+
+    fn restorer(&self, _config: &TerminalConfig) -> Restorer {
+        Arc::new(|| Ok(()))
+    }
+}
+
+/// Fails if the application requested input features, which the backend cannot enable.
+#[cfg(any(all(not(windows), feature = "termion"), feature = "termwiz"))]
+fn reject_input_features(backend: &str, config: &TerminalConfig) -> io::Result<()> {
+    let requested: Vec<&str> = [
+        ("mouse capture", config.mouse_capture),
+        ("bracketed paste", config.bracketed_paste),
+        ("focus change", config.focus_change),
+        (
+            "keyboard enhancement",
+            config.keyboard_enhancement.is_some(),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(feature, enabled)| enabled.then_some(feature))
+    .collect();
+
+    if requested.is_empty() {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{backend} backend cannot enable {}", requested.join(", ")),
+    ))
+}
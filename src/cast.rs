@@ -0,0 +1,154 @@
+//! This module contains [`CastRecorder`] writing drawn frames as an [asciinema v2](https://docs.asciinema.org/manual/asciicast/v2/)
+//! `.cast` file, which can be played with `asciinema play` or embedded in documentation.
+//!
+//! Running application records its frames with [`App::cast_to()`](crate::app::App::cast_to). Frames are drawn also
+//! by [`Harness`](crate::testing::Harness), so casts of scripted sessions can be generated in CI without a real terminal:
+//!
+//! ```no_run
+//! # use crossterm::event::KeyCode;
+//! # use ratatui::{buffer::Buffer, layout::Rect};
+//! # use ratatuio::{app::App, testing::Harness, view::View};
+//! # struct MainPage;
+//! # impl View for MainPage {
+//! #     fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+//! # }
+//! let app = App::new(MainPage).cast_to("demo.cast");
+//! let mut harness = Harness::new(app, 80, 24)?;
+//! harness.text("hello").key(KeyCode::Enter).run()?;
+//! # Ok::<(), ratatuio::Error>(())
+//! ```
+
+use crate::ansi;
+use ratatui::buffer::Buffer;
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+/// Writes frames into an asciinema v2 `.cast` stream, as differences between consecutive frames.
+///
+/// Header is written with the size of the first frame. When size of a frame changes, resize event is written and
+/// the whole frame is redrawn.
+///
+/// ```
+/// # use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+/// # use ratatuio::{cast::CastRecorder, testing, view::View};
+/// # use std::time::Duration;
+/// # struct MainPage;
+/// # impl View for MainPage {
+/// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+/// #         "Hi".render(area, buf);
+/// #     }
+/// # }
+/// let mut cast = CastRecorder::new(Vec::new());
+/// cast.frame_at(Duration::ZERO, &testing::render_view(&MainPage, 4, 1))?;
+///
+/// let cast = String::from_utf8(cast.into_inner()).unwrap();
+/// let mut lines = cast.lines();
+/// assert_eq!(lines.next(), Some(r#"{"version": 2, "width": 4, "height": 1}"#));
+/// assert!(lines.next().unwrap().starts_with(r#"[0.000000, "o", ""#));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct CastRecorder<W: Write> {
+    writer: W,
+    start: Instant,
+    previous: Option<Buffer>,
+}
+
+impl<W: Write> CastRecorder<W> {
+    /// Creates new recorder writing into the provided writer, ex. a file.
+    pub fn new(writer: W) -> Self {
+        CastRecorder {
+            writer,
+            start: Instant::now(),
+            previous: None,
+        }
+    }
+
+    /// Writes the frame with time since the recorder was created.
+    pub fn frame(&mut self, buffer: &Buffer) -> io::Result<()> {
+        self.frame_at(self.start.elapsed(), buffer)
+    }
+
+    /// Writes the frame with the provided time, ex. to generate casts with deterministic timing.
+    ///
+    /// Frames without any change since the previous frame are not written. Frames of inline viewports are recorded
+    /// relative to their area, which starts below the shell prompt:
+    ///
+    /// ```
+    /// # use ratatui::{buffer::Buffer, layout::Rect, style::Style};
+    /// # use ratatuio::cast::CastRecorder;
+    /// # use std::time::Duration;
+    /// let mut buffer = Buffer::empty(Rect::new(0, 8, 4, 2));
+    /// buffer.set_string(0, 9, "Hi", Style::new());
+    ///
+    /// let mut cast = CastRecorder::new(Vec::new());
+    /// cast.frame_at(Duration::ZERO, &buffer)?;
+    ///
+    /// let cast = String::from_utf8(cast.into_inner()).unwrap();
+    /// assert!(cast.starts_with(r#"{"version": 2, "width": 4, "height": 2}"#));
+    /// assert!(cast.contains(r"\u001b[2;1H\u001b[0mHi"));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Parameters:
+    /// - `time`: Time since the beginning of the cast, must not be earlier than that of the previous frame.
+    /// - `buffer`: Drawn frame.
+    pub fn frame_at(&mut self, time: Duration, buffer: &Buffer) -> io::Result<()> {
+        let time = time.as_secs_f64();
+        let (width, height) = (buffer.area.width, buffer.area.height);
+
+        match &self.previous {
+            None => writeln!(
+                self.writer,
+                r#"{{"version": 2, "width": {width}, "height": {height}}}"#
+            )?,
+            Some(previous) if previous.area != buffer.area => {
+                writeln!(self.writer, r#"[{time:.6}, "r", "{width}x{height}"]"#)?
+            }
+            Some(_) => {}
+        }
+
+        let mut output = String::new();
+        if self.previous.is_none() {
+            output.push_str(ansi::HIDE_CURSOR);
+        }
+        output.push_str(&ansi::diff(self.previous.as_ref(), buffer));
+
+        if !output.is_empty() {
+            writeln!(
+                self.writer,
+                r#"[{time:.6}, "o", "{}"]"#,
+                escape_json(&output)
+            )?;
+            self.writer.flush()?;
+        }
+
+        self.previous = Some(buffer.clone());
+        Ok(())
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
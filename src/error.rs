@@ -0,0 +1,99 @@
+//! See [`Error`].
+
+use std::{fmt, io, sync::PoisonError};
+
+/// A specialized [`Result`](std::result::Result) type for ratatuio operations.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors returned by public functions of ratatuio.
+///
+/// Error can be converted into [`io::Error`], so functions returning [`Error`] can be used with `?` inside
+/// [`View::handle_events()`](crate::view::View::handle_events) and other view methods.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Default application was not initialized with [`app::init()`](crate::app::init) or was already run.
+    NotInitialized,
+    /// Shared lock was poisoned by a thread that panicked while holding it.
+    LockPoisoned,
+    /// An error occurred while drawing on the terminal or while handling the events.
+    Io(io::Error),
+    /// Application has no view to draw or to send events to.
+    ViewMissing,
+    /// No route registered in [`router`](crate::router) matches the path.
+    RouteNotFound(String),
+    /// No application state of the requested type was set.
+    StateMissing(&'static str),
+    /// Value provided to [`AppContext::quit_with()`](crate::app::AppContext::quit_with) is not of the requested type.
+    InvalidExitValue(&'static str),
+    /// Application already exited and no longer accepts messages.
+    Closed,
+    /// Text could not be parsed as a [`Recording`](crate::recording::Recording).
+    InvalidRecording {
+        /// Number of the invalid line, starting from 1.
+        line: usize,
+        /// Description of the problem.
+        message: String,
+    },
+    /// Key or keymap configuration could not be parsed, see [`keymap`](crate::keymap).
+    InvalidKeymap(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotInitialized => write!(
+                f,
+                "application is not initialized, did you run app::init()?"
+            ),
+            Error::LockPoisoned => write!(f, "shared lock was poisoned"),
+            Error::Io(err) => write!(f, "{err}"),
+            Error::ViewMissing => write!(f, "application has no view"),
+            Error::RouteNotFound(path) => write!(f, "no route matches path '{path}'"),
+            Error::StateMissing(name) => write!(f, "application state of type {name} is not set"),
+            Error::InvalidExitValue(name) => write!(f, "exit value is not of type {name}"),
+            Error::Closed => write!(f, "application already exited"),
+            Error::InvalidRecording { line, message } => {
+                write!(f, "invalid recording at line {line}: {message}")
+            }
+            Error::InvalidKeymap(message) => write!(f, "invalid keymap: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl<T> From<PoisonError<T>> for Error {
+    fn from(_: PoisonError<T>) -> Self {
+        Error::LockPoisoned
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            Error::RouteNotFound(_) | Error::StateMissing(_) => {
+                io::Error::new(io::ErrorKind::NotFound, err)
+            }
+            Error::InvalidExitValue(_)
+            | Error::InvalidRecording { .. }
+            | Error::InvalidKeymap(_) => io::Error::new(io::ErrorKind::InvalidData, err),
+            Error::Closed => io::Error::new(io::ErrorKind::BrokenPipe, err),
+            _ => io::Error::other(err),
+        }
+    }
+}
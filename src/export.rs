@@ -0,0 +1,324 @@
+//! This module contains functions exporting a rendered [`Buffer`] as a styled screenshot, ex. for documentation or
+//! bug reports:
+//! - [`to_ansi()`] - Text with ANSI escape sequences, which can be printed with `cat` in a terminal.
+//! - [`to_html()`] - HTML `<pre>` element with inline styles.
+//! - [`to_svg()`] - Standalone SVG image.
+//!
+//! Buffer of a view can be rendered with [`testing::render_view()`](crate::testing::render_view), and the screen of
+//! a running application can be saved with a key set by [`App::screenshot_key()`](crate::app::App::screenshot_key).
+//!
+//! ```
+//! # use ratatui::{buffer::Buffer, layout::Rect, style::Stylize, widgets::Widget};
+//! # use ratatuio::{export, testing, view::View};
+//! # struct MainPage;
+//! # impl View for MainPage {
+//! #     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+//! #         "Hi".red().bold().render(area, buf);
+//! #     }
+//! # }
+//! let buffer = testing::render_view(&MainPage, 3, 1);
+//!
+//! assert_eq!(export::to_ansi(&buffer), "\x1b[0;1;31mHi\x1b[0m \x1b[0m\n");
+//! assert!(export::to_html(&buffer).contains(r#"<span style="color:#800000;font-weight:bold">Hi</span>"#));
+//! assert!(export::to_svg(&buffer).starts_with("<svg"));
+//! ```
+
+use crate::{ansi, Error};
+use ratatui::{
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier},
+};
+use std::{fmt::Write, fs, path::Path};
+use unicode_width::UnicodeWidthStr;
+
+/// Color of text with [`Color::Reset`] foreground.
+const DEFAULT_FOREGROUND: &str = "#c0c0c0";
+/// Color of cells with [`Color::Reset`] background.
+const DEFAULT_BACKGROUND: &str = "#000000";
+
+const SVG_FONT_SIZE: f32 = 14.0;
+const SVG_CELL_WIDTH: f32 = 8.4;
+const SVG_LINE_HEIGHT: f32 = 18.0;
+
+/// Returns content of the buffer as text with ANSI escape sequences setting colors and modifiers, one line per row.
+pub fn to_ansi(buffer: &Buffer) -> String {
+    let mut output = String::new();
+    for row in rows(buffer) {
+        for run in row {
+            output.push_str(&ansi::sgr(run.cell));
+            output.push_str(&run.text);
+        }
+        output.push_str(ansi::RESET);
+        output.push('\n');
+    }
+    output
+}
+
+/// Returns content of the buffer as HTML `<pre>` element, with colors and modifiers set by inline styles.
+pub fn to_html(buffer: &Buffer) -> String {
+    let mut html = format!(
+        r#"<pre style="background:{DEFAULT_BACKGROUND};color:{DEFAULT_FOREGROUND};font-family:monospace;line-height:1.2">"#
+    );
+
+    for (y, row) in rows(buffer).into_iter().enumerate() {
+        if y > 0 {
+            html.push('\n');
+        }
+        for run in row {
+            let style = css(run.cell);
+            if style.is_empty() {
+                html.push_str(&escape_xml(&run.text));
+            } else {
+                let _ = write!(
+                    html,
+                    r#"<span style="{style}">{}</span>"#,
+                    escape_xml(&run.text)
+                );
+            }
+        }
+    }
+
+    html.push_str("</pre>\n");
+    html
+}
+
+/// Returns content of the buffer as standalone SVG image, with one monospace text element per run of equally styled cells.
+pub fn to_svg(buffer: &Buffer) -> String {
+    let width = buffer.area.width as f32 * SVG_CELL_WIDTH;
+    let height = buffer.area.height as f32 * SVG_LINE_HEIGHT;
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">
+<style>text {{ font-family: monospace; font-size: {SVG_FONT_SIZE}px; white-space: pre; }}</style>
+<rect width="100%" height="100%" fill="{DEFAULT_BACKGROUND}"/>
+"#
+    );
+
+    let rows = rows(buffer);
+    for (y, row) in rows.iter().enumerate() {
+        for run in row {
+            if let Some(background) = colors(run.cell).1 {
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{SVG_LINE_HEIGHT}" fill="{background}"/>"#,
+                    run.x as f32 * SVG_CELL_WIDTH,
+                    y as f32 * SVG_LINE_HEIGHT,
+                    run.width as f32 * SVG_CELL_WIDTH,
+                );
+            }
+        }
+    }
+
+    for (y, row) in rows.iter().enumerate() {
+        for run in row.iter().filter(|run| !run.text.trim().is_empty()) {
+            let modifier = run.cell.modifier;
+            let _ = write!(
+                svg,
+                r#"<text x="{}" y="{}" textLength="{}" lengthAdjust="spacingAndGlyphs" fill="{}""#,
+                run.x as f32 * SVG_CELL_WIDTH,
+                y as f32 * SVG_LINE_HEIGHT + SVG_FONT_SIZE,
+                run.width as f32 * SVG_CELL_WIDTH,
+                colors(run.cell).0.as_deref().unwrap_or(DEFAULT_FOREGROUND),
+            );
+            if modifier.contains(Modifier::BOLD) {
+                svg.push_str(r#" font-weight="bold""#);
+            }
+            if modifier.contains(Modifier::ITALIC) {
+                svg.push_str(r#" font-style="italic""#);
+            }
+            if modifier.contains(Modifier::DIM) {
+                svg.push_str(r#" opacity="0.5""#);
+            }
+            if let Some(decoration) = text_decoration(modifier) {
+                let _ = write!(svg, r#" text-decoration="{decoration}""#);
+            }
+            let _ = writeln!(svg, ">{}</text>", escape_xml(&run.text));
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Writes the buffer into the file in format chosen by its extension: `.html`, `.svg`, or ANSI text for any other.
+///
+/// # Returns:
+/// - `Ok(())` if the file was written.
+/// - [`Error::Io`] if the file could not be written.
+pub fn save(buffer: &Buffer, path: impl AsRef<Path>) -> Result<(), Error> {
+    let path = path.as_ref();
+    let content = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("html" | "htm") => to_html(buffer),
+        Some("svg") => to_svg(buffer),
+        _ => to_ansi(buffer),
+    };
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Consecutive cells of a row with the same style.
+struct Run<'a> {
+    /// First cell of the run, providing its style.
+    cell: &'a Cell,
+    text: String,
+    x: u16,
+    /// Width of the run in cells.
+    width: u16,
+}
+
+fn rows(buffer: &Buffer) -> Vec<Vec<Run<'_>>> {
+    let area = buffer.area;
+    (area.top()..area.bottom())
+        .map(|y| {
+            let mut row: Vec<Run<'_>> = Vec::new();
+            let mut hidden = 0;
+            for x in area.left()..area.right() {
+                let cell = &buffer[(x, y)];
+                if hidden > 0 || cell.skip {
+                    // Covered by the preceding wide symbol or skipped by the widget.
+                    hidden -= hidden.min(1);
+                    continue;
+                }
+                let width = cell.symbol().width().max(1) as u16;
+                hidden = width - 1;
+
+                match row.last_mut() {
+                    Some(run) if same_style(run.cell, cell) => {
+                        run.text.push_str(cell.symbol());
+                        run.width += width;
+                    }
+                    _ => row.push(Run {
+                        cell,
+                        text: cell.symbol().to_owned(),
+                        x: x - area.left(),
+                        width,
+                    }),
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+fn same_style(a: &Cell, b: &Cell) -> bool {
+    a.fg == b.fg && a.bg == b.bg && a.modifier == b.modifier
+}
+
+/// Returns foreground and background color of the cell, or `None` for default colors, after applying
+/// [`Modifier::REVERSED`] and [`Modifier::HIDDEN`].
+fn colors(cell: &Cell) -> (Option<String>, Option<String>) {
+    let (mut foreground, mut background) = (hex(cell.fg), hex(cell.bg));
+    if cell.modifier.contains(Modifier::REVERSED) {
+        (foreground, background) = (
+            Some(background.unwrap_or_else(|| DEFAULT_BACKGROUND.to_owned())),
+            Some(foreground.unwrap_or_else(|| DEFAULT_FOREGROUND.to_owned())),
+        );
+    }
+    if cell.modifier.contains(Modifier::HIDDEN) {
+        foreground = Some(
+            background
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BACKGROUND.to_owned()),
+        );
+    }
+    (foreground, background)
+}
+
+fn css(cell: &Cell) -> String {
+    let (foreground, background) = colors(cell);
+    let mut style = Vec::new();
+    if let Some(foreground) = foreground {
+        style.push(format!("color:{foreground}"));
+    }
+    if let Some(background) = background {
+        style.push(format!("background:{background}"));
+    }
+    if cell.modifier.contains(Modifier::BOLD) {
+        style.push("font-weight:bold".to_owned());
+    }
+    if cell.modifier.contains(Modifier::DIM) {
+        style.push("opacity:0.5".to_owned());
+    }
+    if cell.modifier.contains(Modifier::ITALIC) {
+        style.push("font-style:italic".to_owned());
+    }
+    if let Some(decoration) = text_decoration(cell.modifier) {
+        style.push(format!("text-decoration:{decoration}"));
+    }
+    style.join(";")
+}
+
+fn text_decoration(modifier: Modifier) -> Option<&'static str> {
+    match (
+        modifier.contains(Modifier::UNDERLINED),
+        modifier.contains(Modifier::CROSSED_OUT),
+    ) {
+        (true, true) => Some("underline line-through"),
+        (true, false) => Some("underline"),
+        (false, true) => Some("line-through"),
+        (false, false) => None,
+    }
+}
+
+/// Returns the color in `#rrggbb` notation according to the xterm palette, or `None` for [`Color::Reset`].
+fn hex(color: Color) -> Option<String> {
+    const PALETTE: [&str; 16] = [
+        "#000000", "#800000", "#008000", "#808000", "#000080", "#800080", "#008080", "#c0c0c0",
+        "#808080", "#ff0000", "#00ff00", "#ffff00", "#0000ff", "#ff00ff", "#00ffff", "#ffffff",
+    ];
+
+    let index = match color {
+        Color::Reset => return None,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(index) if index < 16 => index,
+        Color::Indexed(index) => return Some(indexed(index)),
+        Color::Rgb(r, g, b) => return Some(format!("#{r:02x}{g:02x}{b:02x}")),
+    };
+    Some(PALETTE[index as usize].to_owned())
+}
+
+/// Returns the color of the 6x6x6 cube or the grayscale ramp of the 256-color palette.
+fn indexed(index: u8) -> String {
+    if index >= 232 {
+        let level = 8 + 10 * (index - 232);
+        return format!("#{level:02x}{level:02x}{level:02x}");
+    }
+
+    let level = |value: u8| if value == 0 { 0 } else { 55 + 40 * value };
+    let index = index - 16;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        level(index / 36),
+        level(index / 6 % 6),
+        level(index % 6)
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
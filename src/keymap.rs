@@ -0,0 +1,1017 @@
+//! This module contains [`Keymap`], which resolves pressed keys to named actions, so views do not have to match on
+//! raw key codes and users can remap keys without changing the application.
+//!
+//! Bindings are grouped into contexts. A view selects its context with
+//! [`View::keymap_context()`](crate::view::View::keymap_context), and keys bound in it or in the [`Keymap::GLOBAL`]
+//! context are passed to [`View::handle_action()`](crate::view::View::handle_action) instead of
+//! [`View::handle_events()`](crate::view::View::handle_events). Keymap is set with
+//! [`App::keymap()`](crate::app::App::keymap).
+//!
+//! ```
+//! # use crossterm::event::KeyCode;
+//! # use ratatui::{buffer::Buffer, layout::Rect};
+//! use ratatuio::{app::{App, AppContext}, keymap::{Action, Keymap}, testing::Harness, view::View};
+//! use std::io;
+//!
+//! struct List {
+//!     selected: usize,
+//! }
+//!
+//! impl View for List {
+//! #   fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+//!     fn keymap_context(&self) -> Option<&str> {
+//!         Some("list")
+//!     }
+//!
+//!     fn handle_action(&mut self, action: &Action, ctx: &AppContext) -> io::Result<()> {
+//!         match action.name.as_str() {
+//!             "list.next" => self.selected += 1,
+//!             "app.quit" => ctx.quit(),
+//!             _ => {}
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let keymap = Keymap::new()
+//!     .bind(Keymap::GLOBAL, "app.quit", KeyCode::Char('q'))
+//!     .bind("list", "list.next", KeyCode::Char('j'))
+//!     .bind("list", "list.next", KeyCode::Down);
+//!
+//! let mut harness = Harness::new(App::new(List { selected: 0 }).keymap(keymap), 10, 1)?;
+//! harness.key(KeyCode::Char('j')).key(KeyCode::Down).run()?;
+//! assert_eq!(harness.active_view::<List>().map(|list| list.selected), Some(2));
+//!
+//! harness.key(KeyCode::Char('q')).run()?;
+//! assert!(!harness.is_running());
+//! # Ok::<(), ratatuio::Error>(())
+//! ```
+//!
+//! # Sequences
+//! Actions can be bound to sequences of keys pressed one after another with [`Keymap::bind_sequence()`], ex. `g g` or
+//! `Ctrl+X Ctrl+S`. While a sequence is pending, its keys are shown by
+//! [`AppContext::pending_keys()`](crate::app::AppContext::pending_keys), and if the next key does not come within
+//! [`Keymap::sequence_timeout()`] the sequence is abandoned and its keys are delivered to the view. When a key is
+//! bound both alone and as the beginning of a sequence, like `g` and `g g`, its own action is triggered after the
+//! timeout or when the next key does not continue the sequence. With [`Keymap::count_prefix()`] digits typed before a binding are passed as [`Action::count`].
+//!
+//! ```
+//! # use crossterm::event::KeyCode;
+//! # use ratatui::{buffer::Buffer, layout::Rect};
+//! # use ratatuio::{app::{App, AppContext}, keymap::{Action, Keymap}, testing::Harness, view::View};
+//! # use std::io;
+//! struct Editor {
+//!     line: usize,
+//! }
+//!
+//! impl View for Editor {
+//! #   fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+//!     fn handle_action(&mut self, action: &Action, ctx: &AppContext) -> io::Result<()> {
+//!         match action.name.as_str() {
+//!             "down" => self.line += action.count_or_one(),
+//!             "top" => self.line = 0,
+//!             "goto" => self.line = action.count_or_one() - 1,
+//!             _ => {}
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let keymap = Keymap::new()
+//!     .bind(Keymap::GLOBAL, "down", KeyCode::Char('j'))
+//!     .bind(Keymap::GLOBAL, "goto", KeyCode::Char('g'))
+//!     .bind_sequence(Keymap::GLOBAL, "top", [KeyCode::Char('g'), KeyCode::Char('g')])
+//!     .count_prefix(true);
+//! let mut harness = Harness::new(App::new(Editor { line: 0 }).keymap(keymap), 10, 1)?;
+//! let line = |harness: &Harness| harness.active_view::<Editor>().map(|editor| editor.line);
+//!
+//! harness.text("12j").run()?;
+//! assert_eq!(line(&harness), Some(12));
+//!
+//! harness.text("gg").run()?;
+//! assert_eq!(line(&harness), Some(0));
+//!
+//! harness.text("5g").run()?;
+//! assert_eq!(harness.context().pending_keys().as_deref(), Some("5g"));
+//! harness.expire_keys()?;
+//! assert_eq!(line(&harness), Some(4));
+//! assert_eq!(harness.context().pending_keys(), None);
+//! # Ok::<(), ratatuio::Error>(())
+//! ```
+//!
+//! # Modes
+//! Views emulating vim-like modes switch them with [`AppContext::set_mode()`](crate::app::AppContext::set_mode).
+//! Bindings of context `"editor:insert"` apply only while the view is in mode `"insert"`, and take precedence over
+//! bindings of `"editor"`, which apply in every mode. The same holds for `"global:insert"` and [`Keymap::GLOBAL`].
+//!
+//! ```
+//! # use crossterm::event::{Event, KeyCode};
+//! # use ratatui::{buffer::Buffer, layout::Rect};
+//! # use ratatuio::{app::{App, AppContext}, keymap::{Action, Keymap}, testing::Harness, view::View};
+//! # use std::io;
+//! struct Editor {
+//!     text: String,
+//! }
+//!
+//! impl View for Editor {
+//! #   fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+//!     fn keymap_context(&self) -> Option<&str> {
+//!         Some("editor")
+//!     }
+//!
+//!     fn on_enter(&mut self, ctx: &AppContext) -> io::Result<()> {
+//!         ctx.set_mode("normal");
+//!         Ok(())
+//!     }
+//!
+//!     fn handle_action(&mut self, action: &Action, ctx: &AppContext) -> io::Result<()> {
+//!         match action.name.as_str() {
+//!             "insert" => ctx.set_mode("insert"),
+//!             "normal" => ctx.set_mode("normal"),
+//!             _ => {}
+//!         }
+//!         Ok(())
+//!     }
+//!
+//!     fn handle_events(&mut self, event: &Event, ctx: &AppContext) -> io::Result<()> {
+//!         if let (Event::Key(key), Some("insert")) = (event, ctx.mode().as_deref()) {
+//!             if let KeyCode::Char(c) = key.code {
+//!                 self.text.push(c);
+//!             }
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let keymap = Keymap::new()
+//!     .bind("editor:normal", "insert", KeyCode::Char('i'))
+//!     .bind("editor:insert", "normal", KeyCode::Esc);
+//! let mut harness = Harness::new(App::new(Editor { text: String::new() }).keymap(keymap), 10, 1)?;
+//!
+//! harness.text("xihi").key(KeyCode::Esc).text("x").run()?;
+//! assert_eq!(harness.active_view::<Editor>().map(|editor| editor.text.as_str()), Some("hi"));
+//! assert_eq!(harness.context().mode().as_deref(), Some("normal"));
+//! # Ok::<(), ratatuio::Error>(())
+//! ```
+//!
+//! # Configuration file
+//! With `toml` feature default bindings can be overridden by a user-editable file loaded with [`Keymap::load()`].
+//! Every table is a context, mapping action names to one key or a list of keys. Keys are written like `"ctrl+s"`,
+//! see [`parse_key()`], and sequences like `"g g"`, see [`parse_sequence()`]:
+//!
+//! ```toml
+//! [global]
+//! "app.quit" = ["q", "ctrl+c"]
+//!
+//! [list]
+//! "list.next" = ["j", "down"]
+//! "list.previous" = "k"
+//! "list.first" = ["g g", "home"]
+//!
+//! ["editor:insert"]
+//! "editor.normal" = "esc"
+//! ```
+//!
+//! Actions listed in the file replace all default keys of that action, so an empty list unbinds it.
+
+use crate::Error;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+#[cfg(feature = "toml")]
+use std::{fs, path::Path};
+
+/// Named action resolved from pressed keys, passed to [`View::handle_action()`](crate::view::View::handle_action).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Action {
+    /// Name of the action, ex. `"list.next"`.
+    pub name: String,
+    /// The pressed keys bound to the action, one key unless it was bound to a sequence.
+    pub keys: Vec<KeyEvent>,
+    /// Number typed before the keys, ex. `5` for `5j`, if count prefixes were enabled with
+    /// [`Keymap::count_prefix()`].
+    pub count: Option<usize>,
+}
+
+impl Action {
+    /// Returns the count typed before the keys, or `1` if there was none.
+    pub fn count_or_one(&self) -> usize {
+        self.count.unwrap_or(1)
+    }
+}
+
+/// Key sequences bound to an action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Binding {
+    action: String,
+    sequences: Vec<Vec<KeyEvent>>,
+}
+
+/// Bindings of keys to named actions, grouped into contexts. See [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    /// Bindings of every context, in order they were added.
+    contexts: HashMap<String, Vec<Binding>>,
+    /// Descriptions of actions shown by the help overlay.
+    descriptions: HashMap<String, String>,
+    sequence_timeout: Duration,
+    count_prefix: bool,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap {
+            contexts: HashMap::new(),
+            descriptions: HashMap::new(),
+            sequence_timeout: Duration::from_secs(1),
+            count_prefix: false,
+        }
+    }
+}
+
+/// Bindings matching keys pressed so far.
+struct Lookup<'a> {
+    /// Action bound to exactly the pressed keys.
+    exact: Option<&'a str>,
+    /// `true` if some sequence starts with the pressed keys and continues further.
+    prefix: bool,
+}
+
+impl Keymap {
+    /// Context whose bindings apply to every view. Bindings of the view's own context take precedence.
+    pub const GLOBAL: &'static str = "global";
+
+    /// Creates new keymap without bindings.
+    pub fn new() -> Self {
+        Keymap::default()
+    }
+
+    /// Binds the key to the action in the context. Action can be bound to multiple keys by calling this method again.
+    ///
+    /// # Parameters:
+    /// - `context`: Name of the context, ex. `"list"` or [`Keymap::GLOBAL`].
+    /// - `action`: Name of the action, ex. `"list.next"`.
+    /// - `key`: Key code, or key event with modifiers, ex. `KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)`.
+    pub fn bind(self, context: &str, action: &str, key: impl Into<KeyEvent>) -> Self {
+        self.bind_sequence(context, action, [key])
+    }
+
+    /// Binds the sequence of keys pressed one after another, ex. `g g` or `Ctrl+X Ctrl+S`, to the action in the
+    /// context. See [`Keymap::bind()`].
+    pub fn bind_sequence(
+        mut self,
+        context: &str,
+        action: &str,
+        keys: impl IntoIterator<Item = impl Into<KeyEvent>>,
+    ) -> Self {
+        let sequence = keys.into_iter().map(|key| normalize(key.into())).collect();
+        self.binding_mut(context, action).sequences.push(sequence);
+        self
+    }
+
+    /// Replaces all key sequences bound to the action in the context. Empty `sequences` unbind the action.
+    pub fn set(&mut self, context: &str, action: &str, sequences: Vec<Vec<KeyEvent>>) {
+        self.binding_mut(context, action).sequences = sequences
+            .into_iter()
+            .map(|sequence| sequence.into_iter().map(normalize).collect())
+            .collect();
+    }
+
+    /// Sets description of the action, shown instead of its name by the help overlay, see
+    /// [`App::help_key()`](crate::app::App::help_key).
+    pub fn describe(mut self, action: &str, description: &str) -> Self {
+        self.descriptions
+            .insert(action.to_owned(), description.to_owned());
+        self
+    }
+
+    /// Returns description of the action set with [`Keymap::describe()`].
+    pub fn description(&self, action: &str) -> Option<&str> {
+        self.descriptions.get(action).map(String::as_str)
+    }
+
+    /// Returns actions bound in the context with their key sequences, in order they were bound.
+    ///
+    /// ```
+    /// # use crossterm::event::KeyCode;
+    /// # use ratatuio::keymap::{format_sequence, Keymap};
+    /// let keymap = Keymap::new()
+    ///     .bind("list", "list.next", KeyCode::Char('j'))
+    ///     .bind("list", "list.next", KeyCode::Down);
+    ///
+    /// let bindings: Vec<_> = keymap
+    ///     .bindings("list")
+    ///     .map(|(action, sequences)| (action, sequences.iter().map(|keys| format_sequence(keys)).collect::<Vec<_>>()))
+    ///     .collect();
+    /// assert_eq!(bindings, [("list.next", vec!["j".to_owned(), "down".to_owned()])]);
+    /// ```
+    pub fn bindings(&self, context: &str) -> impl Iterator<Item = (&str, &[Vec<KeyEvent>])> {
+        self.contexts
+            .get(context)
+            .into_iter()
+            .flatten()
+            .filter(|binding| !binding.sequences.is_empty())
+            .map(|binding| (binding.action.as_str(), binding.sequences.as_slice()))
+    }
+
+    /// Sets how long the keymap waits for the next key of a sequence or a count. Defaults to 1 second.
+    ///
+    /// If a key is both bound alone and starts a longer sequence, ex. `g` and `g g`, its action is triggered after
+    /// the timeout or when the next key does not continue the sequence. Keys of a sequence or a count which no binding
+    /// matches are delivered to the view. With `Duration::MAX` sequences wait for the next key indefinitely.
+    ///
+    /// ```
+    /// # use crossterm::event::KeyCode;
+    /// # use ratatuio::keymap::Keymap;
+    /// # use std::time::Duration;
+    /// let keymap = Keymap::new()
+    ///     .bind_sequence(Keymap::GLOBAL, "top", [KeyCode::Char('g'), KeyCode::Char('g')])
+    ///     .sequence_timeout(Duration::from_millis(500));
+    /// ```
+    pub fn sequence_timeout(mut self, timeout: Duration) -> Self {
+        self.sequence_timeout = timeout;
+        self
+    }
+
+    /// Enables numeric count prefixes like in vim, ex. `5j`. Digits typed before a binding are not delivered to the
+    /// view, but passed as [`Action::count`]. `0` starts a count only after another digit, so it can be bound.
+    ///
+    /// Counts start only in contexts and modes with any key bound, so digits typed ex. in an insert mode are
+    /// delivered to the view. Digits not followed by a binding are delivered to the view before the next key, or after
+    /// [`Keymap::sequence_timeout()`] if no key follows them.
+    pub fn count_prefix(mut self, enabled: bool) -> Self {
+        self.count_prefix = enabled;
+        self
+    }
+
+    /// Returns name of the action the key is bound to, looking first in the context and then in [`Keymap::GLOBAL`].
+    /// With a mode, bindings scoped to the mode take precedence, see [module documentation](self#modes).
+    ///
+    /// Only presses and repeats are resolved, key releases are never bound.
+    pub fn resolve(
+        &self,
+        context: Option<&str>,
+        mode: Option<&str>,
+        key: &KeyEvent,
+    ) -> Option<&str> {
+        if key.kind == KeyEventKind::Release {
+            return None;
+        }
+        self.lookup(&Keymap::scopes(context, mode), &[normalize(*key)])
+            .exact
+    }
+
+    /// Returns contexts searched for bindings, from the most specific one.
+    pub(crate) fn scopes(context: Option<&str>, mode: Option<&str>) -> Vec<String> {
+        let mut scopes = Vec::new();
+        for context in context.into_iter().chain([Keymap::GLOBAL]) {
+            if let Some(mode) = mode {
+                scopes.push(format!("{context}:{mode}"));
+            }
+            scopes.push(context.to_owned());
+        }
+        scopes
+    }
+
+    fn binding_mut(&mut self, context: &str, action: &str) -> &mut Binding {
+        let bindings = self.contexts.entry(context.to_owned()).or_default();
+        let index = match bindings.iter().position(|binding| binding.action == action) {
+            Some(index) => index,
+            None => {
+                bindings.push(Binding {
+                    action: action.to_owned(),
+                    sequences: Vec::new(),
+                });
+                bindings.len() - 1
+            }
+        };
+        &mut bindings[index]
+    }
+
+    /// Returns `true` if any of the contexts has a key bound.
+    fn has_bindings(&self, scopes: &[String]) -> bool {
+        scopes
+            .iter()
+            .any(|scope| self.bindings(scope).next().is_some())
+    }
+
+    /// Finds bindings of the normalized keys in the contexts, in order of precedence.
+    fn lookup(&self, scopes: &[String], keys: &[KeyEvent]) -> Lookup<'_> {
+        let mut lookup = Lookup {
+            exact: None,
+            prefix: false,
+        };
+        let bindings = scopes
+            .iter()
+            .filter_map(|scope| self.contexts.get(scope))
+            .flatten();
+
+        for binding in bindings {
+            for sequence in &binding.sequences {
+                if sequence == keys {
+                    lookup.exact = lookup.exact.or(Some(binding.action.as_str()));
+                } else if sequence.starts_with(keys) {
+                    lookup.prefix = true;
+                }
+            }
+        }
+        lookup
+    }
+
+    /// Overrides bindings with the ones in TOML configuration file, see [module documentation](self#configuration-file).
+    ///
+    /// ```no_run
+    /// # use crossterm::event::KeyCode;
+    /// # use ratatuio::keymap::Keymap;
+    /// let keymap = Keymap::new()
+    ///     .bind("list", "list.next", KeyCode::Char('j'))
+    ///     .load("keys.toml")?;
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    ///
+    /// # Returns:
+    /// - `Ok(keymap)` with bindings from the file.
+    /// - [`Error::Io`] if the file could not be read.
+    /// - [`Error::InvalidKeymap`] if the file is not a valid keymap.
+    #[cfg(feature = "toml")]
+    pub fn load(self, path: impl AsRef<Path>) -> Result<Self, Error> {
+        self.merge_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Overrides bindings with the ones in TOML text. See [`Keymap::load()`].
+    ///
+    /// ```
+    /// # use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    /// # use ratatuio::keymap::Keymap;
+    /// let keymap = Keymap::new()
+    ///     .bind("editor", "editor.save", KeyCode::F(2))
+    ///     .merge_toml(r#"editor."editor.save" = ["ctrl+s"]"#)?;
+    ///
+    /// let ctrl_s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+    /// assert_eq!(keymap.resolve(Some("editor"), None, &ctrl_s), Some("editor.save"));
+    /// assert_eq!(keymap.resolve(Some("editor"), None, &KeyCode::F(2).into()), None);
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn merge_toml(mut self, text: &str) -> Result<Self, Error> {
+        let table: toml::Table =
+            toml::from_str(text).map_err(|err| Error::InvalidKeymap(err.to_string()))?;
+
+        for (context, actions) in table {
+            let toml::Value::Table(actions) = actions else {
+                return Err(Error::InvalidKeymap(format!(
+                    "context '{context}' is not a table"
+                )));
+            };
+            for (action, keys) in flatten_actions(String::new(), actions) {
+                let keys = match keys {
+                    toml::Value::String(keys) => vec![parse_sequence(&keys)?],
+                    toml::Value::Array(keys) => keys
+                        .iter()
+                        .map(|keys| match keys.as_str() {
+                            Some(keys) => parse_sequence(keys),
+                            None => Err(Error::InvalidKeymap(format!(
+                                "keys of action '{action}' are not strings"
+                            ))),
+                        })
+                        .collect::<Result<_, _>>()?,
+                    _ => {
+                        return Err(Error::InvalidKeymap(format!(
+                            "keys of action '{action}' are not a string or a list"
+                        )))
+                    }
+                };
+                self.set(&context, &action, keys);
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+/// Keys of a sequence and the count pressed so far, fed by the application loop with every key event.
+#[derive(Debug, Default)]
+pub(crate) struct KeyMatcher {
+    keys: Vec<KeyEvent>,
+    count: Option<usize>,
+    /// Keys of the count and the sequence as they were typed, delivered to the view if no binding matches them.
+    typed: Vec<KeyEvent>,
+    /// When the pending count or sequence times out, `None` if nothing is pending or the timeout is too long to be
+    /// represented.
+    deadline: Option<Instant>,
+}
+
+/// Result of [`KeyMatcher::feed()`], [`KeyMatcher::expire()`] and [`KeyMatcher::flush()`].
+#[derive(Default)]
+pub(crate) struct Matched {
+    /// Actions to deliver to the view, in order.
+    pub(crate) actions: Vec<Action>,
+    /// `true` if the key was consumed by the keymap and should not be delivered to the view.
+    pub(crate) consumed: bool,
+    /// Keys of a count or a sequence which no binding matched, to deliver to the view before the key.
+    pub(crate) unmatched: Vec<KeyEvent>,
+}
+
+impl KeyMatcher {
+    /// Adds the key to the pending sequence and returns actions of completed sequences.
+    pub(crate) fn feed(
+        &mut self,
+        keymap: &Keymap,
+        scopes: &[String],
+        key: &KeyEvent,
+        now: Instant,
+    ) -> Matched {
+        if key.kind == KeyEventKind::Release {
+            return Matched::default();
+        }
+        let event = *key;
+        let key = normalize(event);
+
+        let mut matched = Matched::default();
+        if !self.keys.is_empty() {
+            let mut keys = self.keys.clone();
+            keys.push(key);
+            let lookup = keymap.lookup(scopes, &keys);
+            if lookup.prefix {
+                self.keys = keys;
+                self.typed.push(event);
+                self.deadline = now.checked_add(keymap.sequence_timeout);
+                matched.consumed = true;
+                return matched;
+            }
+            if let Some(name) = lookup.exact {
+                matched.actions.push(self.fire(name, keys));
+                matched.consumed = true;
+                return matched;
+            }
+            // The key does not continue the sequence, so keys pressed so far are resolved alone and the key starts over.
+            matched = self.flush(keymap, scopes);
+        }
+
+        // Digits typed where nothing is bound, ex. in an insert mode, are text rather than a count.
+        if keymap.count_prefix && key.modifiers == KeyModifiers::NONE && keymap.has_bindings(scopes)
+        {
+            if let Some(digit) =
+                key_digit(key.code).filter(|&digit| digit > 0 || self.count.is_some())
+            {
+                let count = self.count.unwrap_or(0).saturating_mul(10);
+                self.count = Some(count.saturating_add(digit));
+                self.typed.push(event);
+                self.deadline = now.checked_add(keymap.sequence_timeout);
+                matched.consumed = true;
+                return matched;
+            }
+        }
+
+        let lookup = keymap.lookup(scopes, &[key]);
+        if lookup.prefix {
+            self.keys = vec![key];
+            self.typed.push(event);
+            self.deadline = now.checked_add(keymap.sequence_timeout);
+        } else if let Some(name) = lookup.exact {
+            matched.actions.push(self.fire(name, vec![key]));
+        } else {
+            self.count = None;
+            self.deadline = None;
+            matched.unmatched.append(&mut self.typed);
+        }
+        matched.consumed = lookup.prefix || lookup.exact.is_some();
+        matched
+    }
+
+    /// Ends the pending count and sequence if they timed out, see [`KeyMatcher::flush()`].
+    pub(crate) fn expire(&mut self, keymap: &Keymap, scopes: &[String], now: Instant) -> Matched {
+        match self.deadline {
+            Some(deadline) if deadline <= now => self.flush(keymap, scopes),
+            _ => Matched::default(),
+        }
+    }
+
+    /// Returns when the pending count or sequence times out, or `None` if nothing is pending or it never times out.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns the count and keys pressed so far, ex. `"2g"`, or `None` if nothing is pending.
+    pub(crate) fn pending(&self) -> Option<String> {
+        if self.keys.is_empty() && self.count.is_none() {
+            return None;
+        }
+        let count = self.count.map(|count| count.to_string());
+        Some(count.unwrap_or_default() + &format_sequence(&self.keys))
+    }
+
+    /// Ends the pending count and sequence, returning action bound to exactly the keys pressed so far, or the typed
+    /// keys if no binding matches them.
+    pub(crate) fn flush(&mut self, keymap: &Keymap, scopes: &[String]) -> Matched {
+        let keys = std::mem::take(&mut self.keys);
+        self.deadline = None;
+        let mut matched = Matched {
+            consumed: true,
+            ..Matched::default()
+        };
+        match keymap.lookup(scopes, &keys).exact {
+            Some(name) => matched.actions.push(self.fire(name, keys)),
+            None => {
+                self.count = None;
+                matched.unmatched.append(&mut self.typed);
+            }
+        }
+        matched
+    }
+
+    fn fire(&mut self, name: &str, keys: Vec<KeyEvent>) -> Action {
+        self.keys.clear();
+        self.typed.clear();
+        self.deadline = None;
+        Action {
+            name: name.to_owned(),
+            keys,
+            count: self.count.take(),
+        }
+    }
+}
+
+fn key_digit(code: KeyCode) -> Option<usize> {
+    match code {
+        KeyCode::Char(c) => c.to_digit(10).map(|digit| digit as usize),
+        _ => None,
+    }
+}
+
+/// Joins keys of nested tables with `.`, so unquoted dotted action names like `list.next = "j"` work too.
+#[cfg(feature = "toml")]
+fn flatten_actions(prefix: String, actions: toml::Table) -> Vec<(String, toml::Value)> {
+    actions
+        .into_iter()
+        .flat_map(|(name, value)| {
+            let name = if prefix.is_empty() {
+                name
+            } else {
+                format!("{prefix}.{name}")
+            };
+            match value {
+                toml::Value::Table(nested) => flatten_actions(name, nested),
+                value => vec![(name, value)],
+            }
+        })
+        .collect()
+}
+
+const KEY_NAMES: [(&str, KeyCode); 16] = [
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("space", KeyCode::Char(' ')),
+];
+
+const MODIFIER_NAMES: [(&str, KeyModifiers); 6] = [
+    ("ctrl", KeyModifiers::CONTROL),
+    ("alt", KeyModifiers::ALT),
+    ("shift", KeyModifiers::SHIFT),
+    ("super", KeyModifiers::SUPER),
+    ("hyper", KeyModifiers::HYPER),
+    ("meta", KeyModifiers::META),
+];
+
+/// Parses key written as modifiers and key name joined with `+`, ex. `"q"`, `"ctrl+s"`, `"alt+shift+enter"` or `"f5"`.
+///
+/// Key names are single characters, `f1` to `f24` and `enter`, `esc`, `tab`, `backtab`, `backspace`, `delete`,
+/// `insert`, `home`, `end`, `pageup`, `pagedown`, `up`, `down`, `left`, `right` and `space`. Modifiers are `ctrl`,
+/// `alt`, `shift`, `super`, `hyper` and `meta`. Names are case insensitive, except for characters.
+///
+/// ```
+/// # use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+/// # use ratatuio::keymap::parse_key;
+/// assert_eq!(parse_key("ctrl+s")?, KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+/// assert_eq!(parse_key("shift+a")?, KeyEvent::new(KeyCode::Char('A'), KeyModifiers::NONE));
+/// assert_eq!(parse_key("ctrl++")?, KeyEvent::new(KeyCode::Char('+'), KeyModifiers::CONTROL));
+/// assert!(parse_key("ctrl+nothing").is_err());
+/// # Ok::<(), ratatuio::Error>(())
+/// ```
+///
+/// # Returns:
+/// - `Ok(key)` with normalized key, see [`Keymap::bind()`].
+/// - [`Error::InvalidKeymap`] if the key could not be parsed.
+pub fn parse_key(text: &str) -> Result<KeyEvent, Error> {
+    let invalid = || Error::InvalidKeymap(format!("invalid key '{text}'"));
+    let (modifiers, name) = match text.strip_suffix('+') {
+        // "+" or "ctrl++" is the plus key itself.
+        Some("") => ("", "+"),
+        Some(modifiers) if modifiers.ends_with('+') => (&modifiers[..modifiers.len() - 1], "+"),
+        _ => text.rsplit_once('+').unwrap_or(("", text)),
+    };
+
+    let modifiers = modifiers
+        .split('+')
+        .filter(|_| !modifiers.is_empty())
+        .try_fold(KeyModifiers::NONE, |modifiers, name| {
+            let name = name.to_ascii_lowercase();
+            MODIFIER_NAMES
+                .iter()
+                .find(|(entry, _)| *entry == name)
+                .map(|(_, modifier)| modifiers | *modifier)
+        })
+        .ok_or_else(invalid)?;
+
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => {
+            let name = name.to_ascii_lowercase();
+            match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(n @ 1..=24) => KeyCode::F(n),
+                _ => KEY_NAMES
+                    .iter()
+                    .find(|(entry, _)| *entry == name)
+                    .map(|(_, code)| *code)
+                    .ok_or_else(invalid)?,
+            }
+        }
+    };
+
+    Ok(normalize(KeyEvent::new(code, modifiers)))
+}
+
+/// Parses sequence of keys separated by whitespace, ex. `"g g"` or `"ctrl+x ctrl+s"`. See [`parse_key()`].
+///
+/// ```
+/// # use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+/// # use ratatuio::keymap::parse_sequence;
+/// assert_eq!(parse_sequence("g g")?, [KeyEvent::from(KeyCode::Char('g')), KeyEvent::from(KeyCode::Char('g'))]);
+/// assert!(parse_sequence(" ").is_err());
+/// # Ok::<(), ratatuio::Error>(())
+/// ```
+///
+/// # Returns:
+/// - `Ok(keys)` with at least one normalized key.
+/// - [`Error::InvalidKeymap`] if the sequence is empty or any key could not be parsed.
+pub fn parse_sequence(text: &str) -> Result<Vec<KeyEvent>, Error> {
+    let keys = text
+        .split_whitespace()
+        .map(parse_key)
+        .collect::<Result<Vec<_>, _>>()?;
+    if keys.is_empty() {
+        return Err(Error::InvalidKeymap(format!("empty key sequence '{text}'")));
+    }
+    Ok(keys)
+}
+
+/// Returns the key written like [`parse_key()`] accepts it, ex. `"ctrl+s"`, to show it in help or status lines.
+///
+/// ```
+/// # use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+/// # use ratatuio::keymap::format_key;
+/// assert_eq!(format_key(&KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT)), "alt+enter");
+/// assert_eq!(format_key(&KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT)), "G");
+/// ```
+pub fn format_key(key: &KeyEvent) -> String {
+    let key = normalize(*key);
+    let mut text = String::new();
+    for (name, modifier) in MODIFIER_NAMES {
+        if key.modifiers.contains(modifier) {
+            text.push_str(name);
+            text.push('+');
+        }
+    }
+    match key.code {
+        KeyCode::F(n) => text.push_str(&format!("f{n}")),
+        code => match KEY_NAMES.iter().find(|(_, entry)| *entry == code) {
+            Some((name, _)) => text.push_str(name),
+            None => match code {
+                KeyCode::Char(c) => text.push(c),
+                code => text.push_str(&format!("{code:?}").to_ascii_lowercase()),
+            },
+        },
+    }
+    text
+}
+
+/// Returns the keys written like [`parse_sequence()`] accepts them, ex. `"ctrl+x ctrl+s"`.
+pub fn format_sequence(keys: &[KeyEvent]) -> String {
+    keys.iter().map(format_key).collect::<Vec<_>>().join(" ")
+}
+
+/// Removes `Shift` from characters, because terminals report it inconsistently and the case of the character already
+/// tells whether it was held. Kind and state of the key are reset, so normalized keys can be compared.
+fn normalize(key: KeyEvent) -> KeyEvent {
+    let (mut code, mut modifiers) = (key.code, key.modifiers);
+    if let KeyCode::Char(c) = code {
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            code = KeyCode::Char(c.to_uppercase().next().unwrap_or(c));
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+    }
+    KeyEvent::new(code, modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap() -> Keymap {
+        Keymap::new()
+            .bind(Keymap::GLOBAL, "down", KeyCode::Char('j'))
+            .bind_sequence(
+                Keymap::GLOBAL,
+                "top",
+                [KeyCode::Char('g'), KeyCode::Char('g')],
+            )
+            .count_prefix(true)
+    }
+
+    fn feed(matcher: &mut KeyMatcher, keymap: &Keymap, code: KeyCode, now: Instant) -> Matched {
+        let scopes = Keymap::scopes(None, None);
+        matcher.feed(keymap, &scopes, &KeyEvent::from(code), now)
+    }
+
+    #[test]
+    fn count_is_delivered_after_timeout() {
+        let (keymap, now) = (keymap(), Instant::now());
+        let mut matcher = KeyMatcher::default();
+
+        assert!(feed(&mut matcher, &keymap, KeyCode::Char('3'), now).consumed);
+        assert_eq!(matcher.pending().as_deref(), Some("3"));
+
+        let scopes = Keymap::scopes(None, None);
+        assert!(matcher.expire(&keymap, &scopes, now).unmatched.is_empty());
+
+        let matched = matcher.expire(&keymap, &scopes, now + keymap.sequence_timeout);
+        assert!(matched.actions.is_empty());
+        assert_eq!(matched.unmatched, [KeyEvent::from(KeyCode::Char('3'))]);
+        assert_eq!(matcher.pending(), None);
+        assert_eq!(matcher.deadline(), None);
+    }
+
+    #[test]
+    fn unmatched_sequence_is_delivered_after_timeout() {
+        let (keymap, now) = (keymap(), Instant::now());
+        let mut matcher = KeyMatcher::default();
+        feed(&mut matcher, &keymap, KeyCode::Char('2'), now);
+        feed(&mut matcher, &keymap, KeyCode::Char('g'), now);
+
+        let scopes = Keymap::scopes(None, None);
+        let matched = matcher.expire(&keymap, &scopes, now + keymap.sequence_timeout);
+        assert_eq!(
+            matched.unmatched,
+            [
+                KeyEvent::from(KeyCode::Char('2')),
+                KeyEvent::from(KeyCode::Char('g'))
+            ]
+        );
+    }
+
+    #[test]
+    fn unmatched_sequence_is_delivered_before_next_key() {
+        let (keymap, now) = (keymap(), Instant::now());
+        let mut matcher = KeyMatcher::default();
+        feed(&mut matcher, &keymap, KeyCode::Char('g'), now);
+
+        let matched = feed(&mut matcher, &keymap, KeyCode::Char('j'), now);
+        assert_eq!(matched.unmatched, [KeyEvent::from(KeyCode::Char('g'))]);
+        assert_eq!(matched.actions.len(), 1);
+        assert_eq!(matched.actions[0].name, "down");
+        assert!(matched.consumed);
+    }
+
+    #[test]
+    fn count_is_passed_to_completed_sequence() {
+        let (keymap, now) = (keymap(), Instant::now());
+        let mut matcher = KeyMatcher::default();
+        feed(&mut matcher, &keymap, KeyCode::Char('1'), now);
+        feed(&mut matcher, &keymap, KeyCode::Char('2'), now);
+        feed(&mut matcher, &keymap, KeyCode::Char('g'), now);
+
+        let matched = feed(&mut matcher, &keymap, KeyCode::Char('g'), now);
+        assert!(matched.unmatched.is_empty());
+        assert_eq!(matched.actions[0].name, "top");
+        assert_eq!(matched.actions[0].count, Some(12));
+    }
+
+    #[test]
+    fn count_is_delivered_in_previous_mode_when_mode_changes() {
+        use crate::{
+            app::{App, AppContext},
+            testing::Harness,
+            view::View,
+        };
+        use crossterm::event::Event;
+        use ratatui::{buffer::Buffer, layout::Rect};
+        use std::{io, time::Duration};
+
+        #[derive(Default)]
+        struct Editor {
+            typed: Vec<(Option<String>, char)>,
+        }
+
+        impl View for Editor {
+            fn render_view(&self, _area: Rect, _buf: &mut Buffer) {}
+
+            fn handle_events(&mut self, event: &Event, ctx: &AppContext) -> io::Result<()> {
+                if let Event::Key(KeyEvent {
+                    code: KeyCode::Char(c),
+                    ..
+                }) = event
+                {
+                    self.typed.push((ctx.mode(), *c));
+                }
+                Ok(())
+            }
+        }
+
+        let app = App::new(Editor::default()).keymap(keymap());
+        let mut harness = Harness::new(app, 10, 1).unwrap();
+        harness.key(KeyCode::Char('3')).run().unwrap();
+        harness.context().set_mode("insert");
+        harness.tick(Duration::ZERO).unwrap();
+
+        let editor = harness.active_view::<Editor>().unwrap();
+        assert_eq!(editor.typed, [(None, '3')]);
+        assert_eq!(harness.context().pending_keys(), None);
+    }
+
+    #[test]
+    fn overflowing_timeout_never_expires() {
+        let keymap = keymap().sequence_timeout(Duration::MAX);
+        let now = Instant::now();
+        let mut matcher = KeyMatcher::default();
+        feed(&mut matcher, &keymap, KeyCode::Char('g'), now);
+
+        assert_eq!(matcher.deadline(), None);
+        let scopes = Keymap::scopes(None, None);
+        let matched = matcher.expire(&keymap, &scopes, now + Duration::from_secs(3600));
+        assert!(matched.actions.is_empty() && matched.unmatched.is_empty());
+        assert_eq!(matcher.pending().as_deref(), Some("g"));
+    }
+
+    #[test]
+    fn digits_without_binding_are_delivered_in_insert_mode() {
+        use crate::{
+            app::{App, AppContext},
+            testing::Harness,
+            view::View,
+        };
+        use crossterm::event::Event;
+        use ratatui::{buffer::Buffer, layout::Rect};
+        use std::io;
+
+        struct Editor {
+            text: String,
+        }
+
+        impl View for Editor {
+            fn render_view(&self, _area: Rect, _buf: &mut Buffer) {}
+
+            fn keymap_context(&self) -> Option<&str> {
+                Some("editor")
+            }
+
+            fn on_enter(&mut self, ctx: &AppContext) -> io::Result<()> {
+                ctx.set_mode("normal");
+                Ok(())
+            }
+
+            fn handle_action(&mut self, action: &Action, ctx: &AppContext) -> io::Result<()> {
+                ctx.set_mode(action.name.as_str());
+                Ok(())
+            }
+
+            fn handle_events(&mut self, event: &Event, ctx: &AppContext) -> io::Result<()> {
+                if let (Event::Key(key), Some("insert")) = (event, ctx.mode().as_deref()) {
+                    if let KeyCode::Char(c) = key.code {
+                        self.text.push(c);
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        let keymap = Keymap::new()
+            .bind("editor:normal", "insert", KeyCode::Char('i'))
+            .bind("editor:insert", "normal", KeyCode::Esc)
+            .count_prefix(true);
+        let app = App::new(Editor {
+            text: String::new(),
+        })
+        .keymap(keymap);
+        let mut harness = Harness::new(app, 10, 1).unwrap();
+
+        harness.text("i2hi").key(KeyCode::Esc).run().unwrap();
+        let editor = harness.active_view::<Editor>().unwrap();
+        assert_eq!(editor.text, "2hi");
+    }
+}
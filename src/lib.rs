@@ -25,6 +25,9 @@
 //! Implement [`view::View`] trait and provide the method [`view::View::render_view()`].
 //!
 //! ```
+//! # use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+//! # use ratatuio::view::View;
+//! # struct MainPage;
 //! impl View for MainPage{
 //!     fn render_view(&self, area: Rect, buf: &mut Buffer){
 //!         "Hello World!".render(area, buf);
@@ -34,36 +37,46 @@
 //!
 //! And finally initialize and run the app with created MainPage view in the main method.
 //!
-//! ```
-//! fn main() -> io::Result<()> {
-//!     app::init(MainPage);
+//! ```no_run
+//! # use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+//! # use ratatuio::{app, view::View};
+//! # use std::io;
+//! # struct MainPage;
+//! # impl View for MainPage{
+//! #     fn render_view(&self, area: Rect, buf: &mut Buffer){
+//! #         "Hello World!".render(area, buf);
+//! #     }
+//! # }
+//! fn main() -> Result<(), ratatuio::Error> {
+//!     app::init(MainPage)?;
 //!     app::run()
 //! }
 //! ```
 //!
+//! Functions of module [`app`] return [`Error`] instead of panicking, so the application can recover or report the problem cleanly.
+//!
 //! ### Adding event handler
 //!
 //! Above example will run in console however it will be impossible to close. To implement closing the application we first need to handle key press event.
 //!
-//! In trait [`view::View`] there is an optional method to implement [`view::View::handle_events()`] with parameter [`crossterm::event::Event`].
+//! In trait [`view::View`] there is an optional method to implement [`view::View::handle_events()`] with parameters [`crossterm::event::Event`] and [`app::AppContext`].
 //! We can implement this method and using Rusts' match statement catch when user is pressing key 'q' or 'Q':
 //!
 //! ```
+//! # use crossterm::event::{Event, KeyCode, KeyEventKind};
+//! # use ratatui::{buffer::Buffer, layout::Rect};
+//! # use ratatuio::{app::AppContext, view::View};
+//! # use std::io;
+//! # struct MainPage;
 //! impl View for MainPage{
 //!     //...
+//! #   fn render_view(&self, area: Rect, buf: &mut Buffer){}
 //!
-//!     fn handle_events(&mut self, event: &Event) -> io::Result<()> {
+//!     fn handle_events(&mut self, event: &Event, ctx: &AppContext) -> io::Result<()> {
 //!         match event {
 //!             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
 //!                 match key_event.code {
-//!                     KeyCode::Char('q') | KeyCode::Char('Q') => {
-//!                         app::APPLICATION
-//!                             .write()
-//!                             .unwrap()
-//!                             .as_mut()
-//!                             .unwrap()
-//!                             .is_running = false
-//!                     },
+//!                     KeyCode::Char('q') | KeyCode::Char('Q') => ctx.quit(),
 //!                     _ => {}
 //!                 }
 //!             }
@@ -71,9 +84,42 @@
 //!         }
 //!         Ok(())
 //!     }
+//! }
+//! ```
+//!
+//! Inside `handle_events` we are checking when user is pressing key 'q' or 'Q' after which we are calling [`app::AppContext::quit()`], which will exit out of the program loop on the next iteration.
+//! Outside of views the same can be done with [`app::quit()`].
+//!
+//! To return a value from the application, ex. an item chosen by the user, call [`app::AppContext::quit_with()`] and start the loop with [`app::run_with_result()`].
+//!
+//! ## Running without global state
+//!
+//! Functions [`app::init()`] and [`app::run()`] operate on a default application instance. The same can be achieved with an owned [`app::App`] handle,
+//! which allows to run multiple independent applications in one process:
+//!
+//! ```no_run
+//! # use ratatui::{buffer::Buffer, layout::Rect};
+//! # use ratatuio::{app::App, view::View};
+//! # use std::io;
+//! # struct MainPage;
+//! # impl View for MainPage{
+//! #     fn render_view(&self, area: Rect, buf: &mut Buffer){}
+//! # }
+//! fn main() -> Result<(), ratatuio::Error> {
+//!     App::new(MainPage).run()
+//! }
 //! ```
-//! 
-//! Inside `handle_events` we are checking when user is pressing key 'q' or 'Q' after which we are accessing application state [`app::APPLICATION`] and editing its 'is_running' value, which will exit out of the program loop on the next iteration.
 
+mod ansi;
 pub mod app;
+pub mod backend;
+pub mod cast;
+pub mod error;
+pub mod export;
+pub mod keymap;
+pub mod recording;
+pub mod router;
+pub mod testing;
 pub mod view;
+
+pub use error::{Error, Result};
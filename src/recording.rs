@@ -0,0 +1,509 @@
+//! This module contains [`Recording`] of terminal events, which can be captured from a running application with
+//! [`App::record_to()`](crate::app::App::record_to) and fed back into it with [`App::replay()`](crate::app::App::replay)
+//! or into a headless [`Harness`](crate::testing::Harness) with [`Harness::replay()`](crate::testing::Harness::replay).
+//!
+//! Recordings are stored as plain text, one event per line prefixed with seconds since the start of the
+//! application, so they can be attached to bug reports and edited by hand:
+//!
+//! ```text
+//! # ratatuio recording v1
+//! size 80 24
+//! 0.512000 key char:j NONE press NONE
+//! 1.048250 key char:s CONTROL press NONE
+//! 2.300000 resize 120 40
+//! 2.750000 mouse down:left 10 4 NONE
+//! 3.125000 paste hello\u{20}world
+//! ```
+//!
+//! ```
+//! use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+//! use ratatuio::recording::Recording;
+//! use std::time::Duration;
+//!
+//! let mut recording = Recording::new();
+//! recording.size = Some((80, 24));
+//! recording.push(
+//!     Duration::from_millis(500),
+//!     Event::Key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)),
+//! );
+//!
+//! let parsed: Recording = recording.to_string().parse()?;
+//! assert_eq!(parsed, recording);
+//! # Ok::<(), ratatuio::Error>(())
+//! ```
+
+use crate::Error;
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, MediaKeyCode,
+    ModifierKeyCode, MouseButton, MouseEvent, MouseEventKind,
+};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+const HEADER: &str = "# ratatuio recording v1";
+
+/// A single event of a [`Recording`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedEvent {
+    /// Time since the start of the application when the event occurred.
+    pub at: Duration,
+    /// The event read from the terminal.
+    pub event: Event,
+}
+
+/// Terminal events with timestamps, in order they were read. See [module documentation](self) for the file format.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// Size of the terminal when the recording started. Later changes are recorded as [`Event::Resize`].
+    pub size: Option<(u16, u16)>,
+    /// Recorded events, ordered by [`RecordedEvent::at`].
+    pub events: Vec<RecordedEvent>,
+}
+
+impl Recording {
+    /// Creates new empty recording.
+    pub fn new() -> Self {
+        Recording::default()
+    }
+
+    /// Appends the event to the recording.
+    ///
+    /// # Parameters:
+    /// - `at`: Time since the start of the application, must not be earlier than of the previous event.
+    /// - `event`: Recorded event.
+    pub fn push(&mut self, at: Duration, event: Event) {
+        self.events.push(RecordedEvent { at, event });
+    }
+
+    /// Reads recording from the file.
+    ///
+    /// # Returns:
+    /// - `Ok(recording)` if the file was read and parsed.
+    /// - [`Error::Io`] if the file could not be read.
+    /// - [`Error::InvalidRecording`] if the file is not a valid recording.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Writes recording to the file, replacing its content.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        if let Some((width, height)) = self.size {
+            writeln!(f, "size {width} {height}")?;
+        }
+        for recorded in &self.events {
+            writeln!(f, "{}", EventLine(recorded.at, &recorded.event))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Recording {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut recording = Recording::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let invalid = |message: String| Error::InvalidRecording {
+                line: index + 1,
+                message,
+            };
+
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens.as_slice() {
+                [] => {}
+                [first, ..] if first.starts_with('#') => {}
+                ["size", width, height] => {
+                    let width = parse_number(width).map_err(invalid)?;
+                    let height = parse_number(height).map_err(invalid)?;
+                    recording.size = Some((width, height));
+                }
+                ["size", ..] => return Err(invalid(format!("invalid size '{}'", line.trim()))),
+                [at, event @ ..] => {
+                    let at = parse_duration(at).map_err(invalid)?;
+                    let event = parse_event(event).map_err(invalid)?;
+                    recording.push(at, event);
+                }
+            }
+        }
+
+        Ok(recording)
+    }
+}
+
+/// Appends events read by the running application to a file, flushing after every event, so the recording is
+/// complete even if the application crashes.
+pub(crate) struct Recorder {
+    file: BufWriter<File>,
+    start: Instant,
+}
+
+impl Recorder {
+    pub(crate) fn create(path: &Path, size: (u16, u16)) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{HEADER}")?;
+        writeln!(file, "size {} {}", size.0, size.1)?;
+        file.flush()?;
+
+        Ok(Recorder {
+            file,
+            start: Instant::now(),
+        })
+    }
+
+    pub(crate) fn record(&mut self, event: &Event) -> io::Result<()> {
+        writeln!(self.file, "{}", EventLine(self.start.elapsed(), event))?;
+        self.file.flush()
+    }
+}
+
+/// Formats a single line of the recording.
+struct EventLine<'a>(Duration, &'a Event);
+
+impl fmt::Display for EventLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:06} ", self.0.as_secs(), self.0.subsec_micros())?;
+        match self.1 {
+            Event::Key(key) => write!(
+                f,
+                "key {} {} {} {}",
+                key_code_name(key.code),
+                flag_names(key.modifiers.iter_names()),
+                match key.kind {
+                    KeyEventKind::Press => "press",
+                    KeyEventKind::Repeat => "repeat",
+                    KeyEventKind::Release => "release",
+                },
+                flag_names(key.state.iter_names()),
+            ),
+            Event::Mouse(mouse) => write!(
+                f,
+                "mouse {} {} {} {}",
+                mouse_kind_name(mouse.kind),
+                mouse.column,
+                mouse.row,
+                flag_names(mouse.modifiers.iter_names()),
+            ),
+            Event::Resize(width, height) => write!(f, "resize {width} {height}"),
+            Event::Paste(text) => write!(f, "paste {}", escape(text)),
+            Event::FocusGained => write!(f, "focus-gained"),
+            Event::FocusLost => write!(f, "focus-lost"),
+        }
+    }
+}
+
+const KEY_CODES: [(&str, KeyCode); 23] = [
+    ("backspace", KeyCode::Backspace),
+    ("enter", KeyCode::Enter),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("null", KeyCode::Null),
+    ("esc", KeyCode::Esc),
+    ("capslock", KeyCode::CapsLock),
+    ("scrolllock", KeyCode::ScrollLock),
+    ("numlock", KeyCode::NumLock),
+    ("printscreen", KeyCode::PrintScreen),
+    ("pause", KeyCode::Pause),
+    ("menu", KeyCode::Menu),
+    ("keypadbegin", KeyCode::KeypadBegin),
+];
+
+const MEDIA_KEY_CODES: [(&str, MediaKeyCode); 13] = [
+    ("play", MediaKeyCode::Play),
+    ("pause", MediaKeyCode::Pause),
+    ("playpause", MediaKeyCode::PlayPause),
+    ("reverse", MediaKeyCode::Reverse),
+    ("stop", MediaKeyCode::Stop),
+    ("fastforward", MediaKeyCode::FastForward),
+    ("rewind", MediaKeyCode::Rewind),
+    ("tracknext", MediaKeyCode::TrackNext),
+    ("trackprevious", MediaKeyCode::TrackPrevious),
+    ("record", MediaKeyCode::Record),
+    ("lowervolume", MediaKeyCode::LowerVolume),
+    ("raisevolume", MediaKeyCode::RaiseVolume),
+    ("mutevolume", MediaKeyCode::MuteVolume),
+];
+
+const MODIFIER_KEY_CODES: [(&str, ModifierKeyCode); 14] = [
+    ("leftshift", ModifierKeyCode::LeftShift),
+    ("leftcontrol", ModifierKeyCode::LeftControl),
+    ("leftalt", ModifierKeyCode::LeftAlt),
+    ("leftsuper", ModifierKeyCode::LeftSuper),
+    ("lefthyper", ModifierKeyCode::LeftHyper),
+    ("leftmeta", ModifierKeyCode::LeftMeta),
+    ("rightshift", ModifierKeyCode::RightShift),
+    ("rightcontrol", ModifierKeyCode::RightControl),
+    ("rightalt", ModifierKeyCode::RightAlt),
+    ("rightsuper", ModifierKeyCode::RightSuper),
+    ("righthyper", ModifierKeyCode::RightHyper),
+    ("rightmeta", ModifierKeyCode::RightMeta),
+    ("isolevel3shift", ModifierKeyCode::IsoLevel3Shift),
+    ("isolevel5shift", ModifierKeyCode::IsoLevel5Shift),
+];
+
+fn name_of<T: PartialEq + Copy>(table: &[(&'static str, T)], value: T) -> &'static str {
+    table
+        .iter()
+        .find(|(_, entry)| *entry == value)
+        .map_or("unknown", |(name, _)| name)
+}
+
+fn value_of<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+    table
+        .iter()
+        .find(|(entry, _)| *entry == name)
+        .map(|(_, value)| *value)
+}
+
+fn key_code_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(c) => format!("char:{}", escape(&c.to_string())),
+        KeyCode::F(n) => format!("f{n}"),
+        KeyCode::Media(media) => format!("media:{}", name_of(&MEDIA_KEY_CODES, media)),
+        KeyCode::Modifier(modifier) => {
+            format!("modifier:{}", name_of(&MODIFIER_KEY_CODES, modifier))
+        }
+        code => name_of(&KEY_CODES, code).to_owned(),
+    }
+}
+
+fn parse_key_code(token: &str) -> Result<KeyCode, String> {
+    let code = if let Some(c) = token.strip_prefix("char:") {
+        let c = unescape(c)?;
+        let mut chars = c.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(KeyCode::Char(c)),
+            _ => None,
+        }
+    } else if let Some(media) = token.strip_prefix("media:") {
+        value_of(&MEDIA_KEY_CODES, media).map(KeyCode::Media)
+    } else if let Some(modifier) = token.strip_prefix("modifier:") {
+        value_of(&MODIFIER_KEY_CODES, modifier).map(KeyCode::Modifier)
+    } else if let Some(n) = token.strip_prefix('f').and_then(|n| n.parse().ok()) {
+        Some(KeyCode::F(n))
+    } else {
+        value_of(&KEY_CODES, token)
+    };
+
+    code.ok_or_else(|| format!("unknown key '{token}'"))
+}
+
+fn mouse_kind_name(kind: MouseEventKind) -> String {
+    let button = |button: MouseButton| match button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+    };
+
+    match kind {
+        MouseEventKind::Down(b) => format!("down:{}", button(b)),
+        MouseEventKind::Up(b) => format!("up:{}", button(b)),
+        MouseEventKind::Drag(b) => format!("drag:{}", button(b)),
+        MouseEventKind::Moved => "moved".to_owned(),
+        MouseEventKind::ScrollDown => "scroll-down".to_owned(),
+        MouseEventKind::ScrollUp => "scroll-up".to_owned(),
+        MouseEventKind::ScrollLeft => "scroll-left".to_owned(),
+        MouseEventKind::ScrollRight => "scroll-right".to_owned(),
+    }
+}
+
+fn parse_mouse_kind(token: &str) -> Result<MouseEventKind, String> {
+    let button = |name: &str| match name {
+        "left" => Ok(MouseButton::Left),
+        "right" => Ok(MouseButton::Right),
+        "middle" => Ok(MouseButton::Middle),
+        _ => Err(format!("unknown mouse button '{name}'")),
+    };
+
+    match token.split_once(':') {
+        Some(("down", b)) => Ok(MouseEventKind::Down(button(b)?)),
+        Some(("up", b)) => Ok(MouseEventKind::Up(button(b)?)),
+        Some(("drag", b)) => Ok(MouseEventKind::Drag(button(b)?)),
+        _ => match token {
+            "moved" => Ok(MouseEventKind::Moved),
+            "scroll-down" => Ok(MouseEventKind::ScrollDown),
+            "scroll-up" => Ok(MouseEventKind::ScrollUp),
+            "scroll-left" => Ok(MouseEventKind::ScrollLeft),
+            "scroll-right" => Ok(MouseEventKind::ScrollRight),
+            _ => Err(format!("unknown mouse event '{token}'")),
+        },
+    }
+}
+
+/// Joins names of set flags with `+`, or returns `NONE` if no flag is set.
+fn flag_names<T>(names: impl Iterator<Item = (&'static str, T)>) -> String {
+    let names: Vec<&str> = names.map(|(name, _)| name).collect();
+    if names.is_empty() {
+        "NONE".to_owned()
+    } else {
+        names.join("+")
+    }
+}
+
+fn parse_flags<T: std::ops::BitOr<Output = T>>(
+    token: &str,
+    empty: T,
+    from_name: fn(&str) -> Option<T>,
+) -> Result<T, String> {
+    token.split('+').try_fold(empty, |flags, name| {
+        from_name(name)
+            .map(|flag| flags | flag)
+            .ok_or_else(|| format!("unknown flag '{name}'"))
+    })
+}
+
+fn parse_event(tokens: &[&str]) -> Result<Event, String> {
+    match tokens {
+        ["key", code, modifiers, kind, state] => Ok(Event::Key(KeyEvent {
+            code: parse_key_code(code)?,
+            modifiers: parse_flags(modifiers, KeyModifiers::NONE, KeyModifiers::from_name)?,
+            kind: match *kind {
+                "press" => KeyEventKind::Press,
+                "repeat" => KeyEventKind::Repeat,
+                "release" => KeyEventKind::Release,
+                _ => return Err(format!("unknown key event kind '{kind}'")),
+            },
+            state: parse_flags(state, KeyEventState::NONE, KeyEventState::from_name)?,
+        })),
+        ["mouse", kind, column, row, modifiers] => Ok(Event::Mouse(MouseEvent {
+            kind: parse_mouse_kind(kind)?,
+            column: parse_number(column)?,
+            row: parse_number(row)?,
+            modifiers: parse_flags(modifiers, KeyModifiers::NONE, KeyModifiers::from_name)?,
+        })),
+        ["resize", width, height] => Ok(Event::Resize(parse_number(width)?, parse_number(height)?)),
+        ["paste"] => Ok(Event::Paste(String::new())),
+        ["paste", text] => Ok(Event::Paste(unescape(text)?)),
+        ["focus-gained"] => Ok(Event::FocusGained),
+        ["focus-lost"] => Ok(Event::FocusLost),
+        _ => Err(format!("invalid event '{}'", tokens.join(" "))),
+    }
+}
+
+fn parse_number(token: &str) -> Result<u16, String> {
+    token
+        .parse()
+        .map_err(|_| format!("invalid number '{token}'"))
+}
+
+fn parse_duration(token: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid timestamp '{token}'");
+    let (secs, micros) = token.split_once('.').unwrap_or((token, "0"));
+    let secs: u64 = secs.parse().map_err(|_| invalid())?;
+    let micros = format!("{micros:0<6}");
+    if micros.len() != 6 {
+        return Err(invalid());
+    }
+    let micros: u64 = micros.parse().map_err(|_| invalid())?;
+
+    Ok(Duration::from_secs(secs) + Duration::from_micros(micros))
+}
+
+/// Escapes whitespace, control characters and backslashes as `\u{..}`, so text is a single token without line breaks.
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_whitespace() || c.is_control() || c == '\\' {
+                format!("\\u{{{:x}}}", c as u32)
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+fn unescape(token: &str) -> Result<String, String> {
+    let mut text = String::new();
+    let mut rest = token;
+
+    while let Some(start) = rest.find('\\') {
+        text.push_str(&rest[..start]);
+        let escaped = rest[start..]
+            .strip_prefix("\\u{")
+            .and_then(|escaped| escaped.split_once('}'))
+            .and_then(|(code, tail)| {
+                let c = char::from_u32(u32::from_str_radix(code, 16).ok()?)?;
+                Some((c, tail))
+            });
+        let Some((c, tail)) = escaped else {
+            return Err(format!("invalid escape in '{token}'"));
+        };
+        text.push(c);
+        rest = tail;
+    }
+    text.push_str(rest);
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the recording and returns the reported line number and message of the error.
+    fn parse_error(text: &str) -> (usize, String) {
+        match text.parse::<Recording>() {
+            Err(Error::InvalidRecording { line, message }) => (line, message),
+            result => panic!("expected invalid recording, got {result:?}"),
+        }
+    }
+
+    const VALID: &str =
+        "# ratatuio recording v1\nsize 80 24\n0.512000 key char:j NONE press NONE\n";
+
+    #[test]
+    fn malformed_timestamp_is_reported_with_its_line() {
+        let (line, message) = parse_error(&format!("{VALID}1.5s key char:k NONE press NONE\n"));
+        assert_eq!(line, 4);
+        assert_eq!(message, "invalid timestamp '1.5s'");
+
+        let (line, _) = parse_error(&format!("{VALID}1.1234567 focus-lost\n"));
+        assert_eq!(line, 4);
+    }
+
+    #[test]
+    fn unknown_event_kind_is_reported_with_its_line() {
+        let (line, message) = parse_error(&format!("{VALID}\n1.000000 scroll 4 2\n"));
+        assert_eq!(line, 5);
+        assert_eq!(message, "invalid event 'scroll 4 2'");
+
+        let (line, message) = parse_error(&format!("{VALID}1.000000 key char:k NONE hold NONE\n"));
+        assert_eq!(line, 4);
+        assert_eq!(message, "unknown key event kind 'hold'");
+    }
+
+    #[test]
+    fn truncated_line_is_reported_with_its_line() {
+        let (line, message) = parse_error(&format!("{VALID}1.000000 key char:k NONE"));
+        assert_eq!(line, 4);
+        assert_eq!(message, "invalid event 'key char:k NONE'");
+
+        let (line, message) = parse_error("# ratatuio recording v1\nsize 80\n");
+        assert_eq!(line, 2);
+        assert_eq!(message, "invalid size 'size 80'");
+    }
+}
@@ -0,0 +1,255 @@
+//! This module contains the router used to construct [`View`]s from named paths.
+//!
+//! Instead of importing and constructing concrete [`View`] types at every call site, views can be
+//! registered under a path in a [`Router`] of the application and later opened with
+//! [`AppContext::navigate()`](crate::app::AppContext::navigate). The default application created by
+//! [`app::init()`](crate::app::init) uses routes registered with [`register()`] and opened with
+//! [`app::navigate()`](crate::app::navigate).
+//!
+//! # Paths
+//! Path is made out of segments separated by `/`. Segment starting with `:` is a parameter, which
+//! will match any value and pass it to the factory in [`Params`].
+//!
+//! ```
+//! use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+//! use ratatuio::{router, view::View};
+//!
+//! struct EditUser {
+//!     id: u32,
+//! }
+//!
+//! impl View for EditUser {
+//!     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+//!         format!("Editing user {}", self.id).render(area, buf);
+//!     }
+//! }
+//!
+//! router::register("/users/:id/edit", |params| EditUser {
+//!     id: params.parse("id").unwrap_or_default(),
+//! });
+//!
+//! assert!(router::resolve("/users/42/edit").is_ok());
+//! assert!(router::resolve("/users/42").is_err());
+//! ```
+
+use crate::{
+    view::{BoxedView, View},
+    Error,
+};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+type Factory = Arc<dyn Fn(&Params) -> BoxedView + Sync + Send>;
+
+#[derive(Clone)]
+struct Route {
+    segments: Vec<String>,
+    factory: Factory,
+}
+
+/// Routes registered with [`register()`], used by the default application created by [`app::init()`](crate::app::init).
+static ROUTES: RwLock<Router> = RwLock::new(Router::new());
+
+/// Parameters parsed from the path, passed to the factory of matching route.
+///
+/// For route `"/users/:id/edit"` and path `"/users/42/edit"` parameter `id` has value `"42"`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Params(HashMap<String, String>);
+
+impl Params {
+    /// Returns raw value of the parameter or `None` if route does not define it.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    /// Parses value of the parameter into `T`. Returns `None` if parameter is missing or cannot be parsed.
+    pub fn parse<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get(name)?.parse().ok()
+    }
+}
+
+/// Routes of one application, set with [`App::router()`](crate::app::App::router) and resolved by
+/// [`AppContext::navigate()`](crate::app::AppContext::navigate).
+///
+/// ```
+/// # use crossterm::event::{Event, KeyCode};
+/// # use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+/// # use ratatuio::{app::{App, AppContext}, router::Router, testing::Harness, view::View};
+/// # use std::io;
+/// struct Users;
+///
+/// impl View for Users {
+///     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+///         "Users".render(area, buf);
+///     }
+///
+///     fn handle_events(&mut self, _event: &Event, ctx: &AppContext) -> io::Result<()> {
+///         ctx.navigate("/users/42/edit").map_err(io::Error::from)
+///     }
+/// }
+///
+/// struct EditUser {
+///     id: u32,
+/// }
+///
+/// impl View for EditUser {
+///     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+///         format!("Editing user {}", self.id).render(area, buf);
+///     }
+/// }
+///
+/// let router = Router::new().route("/users/:id/edit", |params| EditUser {
+///     id: params.parse("id").unwrap_or_default(),
+/// });
+/// let mut harness = Harness::new(App::new(Users).router(router), 20, 1)?;
+///
+/// harness.key(KeyCode::Enter).run()?;
+/// assert_eq!(harness.lines(), ["Editing user 42     "]);
+/// # Ok::<(), ratatuio::Error>(())
+/// ```
+#[derive(Clone, Default)]
+pub struct Router {
+    /// Routes matched in order of registration.
+    routes: Vec<Route>,
+}
+
+/// Route matching a path, with parameters parsed from it.
+pub(crate) struct Matched {
+    factory: Factory,
+    params: Params,
+}
+
+impl Matched {
+    /// Creates the view with factory of the route. Called without holding any lock, so the factory can register routes.
+    pub(crate) fn create(&self) -> BoxedView {
+        (self.factory)(&self.params)
+    }
+}
+
+impl Router {
+    /// Creates new router without routes.
+    pub const fn new() -> Self {
+        Router { routes: Vec::new() }
+    }
+
+    /// Adds route under the provided path. See [`Router::register()`].
+    pub fn route<T, F>(mut self, path: &str, factory: F) -> Self
+    where
+        T: View + Sync + Send + 'static,
+        F: Fn(&Params) -> T + Sync + Send + 'static,
+    {
+        self.register(path, factory);
+        self
+    }
+
+    /// Registers new route under the provided path.
+    ///
+    /// If the path was already registered its factory is replaced.
+    ///
+    /// # Parameters:
+    /// - `path`: Path of the route, ex. `"/users/:id/edit"`.
+    /// - `factory`: Closure creating a [`View`] from parsed [`Params`].
+    pub fn register<T, F>(&mut self, path: &str, factory: F)
+    where
+        T: View + Sync + Send + 'static,
+        F: Fn(&Params) -> T + Sync + Send + 'static,
+    {
+        let route = Route {
+            segments: segments(path).map(str::to_owned).collect(),
+            factory: Arc::new(move |params| Box::new(factory(params))),
+        };
+
+        match self
+            .routes
+            .iter_mut()
+            .find(|r| r.segments == route.segments)
+        {
+            Some(existing) => *existing = route,
+            None => self.routes.push(route),
+        }
+    }
+
+    /// Creates the [`View`] registered under route matching the provided path.
+    ///
+    /// # Returns:
+    /// - `Ok(view)` created by factory of the first matching route.
+    /// - [`Error::RouteNotFound`] if no route matches the path.
+    pub fn resolve(&self, path: &str) -> Result<Box<dyn View + Sync + Send>, Error> {
+        Ok(self.find(path)?.create().into_view())
+    }
+
+    /// Finds the first route matching the path.
+    pub(crate) fn find(&self, path: &str) -> Result<Matched, Error> {
+        self.routes
+            .iter()
+            .find_map(|route| {
+                route.matches(path).map(|params| Matched {
+                    factory: route.factory.clone(),
+                    params,
+                })
+            })
+            .ok_or_else(|| Error::RouteNotFound(path.to_owned()))
+    }
+}
+
+/// Registers new route under the provided path for the default application. See [`Router::register()`].
+///
+/// Applications created with [`App::new()`](crate::app::App::new) have their own [`Router`] and do not see these
+/// routes.
+pub fn register<T, F>(path: &str, factory: F)
+where
+    T: View + Sync + Send + 'static,
+    F: Fn(&Params) -> T + Sync + Send + 'static,
+{
+    ROUTES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .register(path, factory);
+}
+
+/// Creates the [`View`] registered with [`register()`] under route matching the provided path.
+///
+/// # Returns:
+/// - `Ok(view)` created by factory of the first matching route.
+/// - [`Error::RouteNotFound`] if no route matches the path.
+pub fn resolve(path: &str) -> Result<Box<dyn View + Sync + Send>, Error> {
+    Ok(find(path)?.create().into_view())
+}
+
+/// Finds the route registered with [`register()`] matching the path. The registry is unlocked before returning.
+pub(crate) fn find(path: &str) -> Result<Matched, Error> {
+    ROUTES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .find(path)
+}
+
+impl Route {
+    fn matches(&self, path: &str) -> Option<Params> {
+        let mut params = HashMap::new();
+        let mut values = segments(path);
+
+        for segment in &self.segments {
+            let value = values.next()?;
+            match segment.strip_prefix(':') {
+                Some(name) => {
+                    params.insert(name.to_owned(), value.to_owned());
+                }
+                None if segment != value => return None,
+                None => {}
+            }
+        }
+
+        match values.next() {
+            Some(_) => None,
+            None => Some(Params(params)),
+        }
+    }
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
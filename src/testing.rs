@@ -0,0 +1,446 @@
+//! This module contains a headless [`Harness`] for testing applications in `cargo test`.
+//!
+//! Harness runs the same application loop as [`App::run()`], but draws on ratatui's [`TestBackend`] instead of
+//! the real terminal and reads events from a scripted queue instead of `crossterm::event::read()`.
+//!
+//! ```
+//! use crossterm::event::{Event, KeyCode};
+//! use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+//! use ratatuio::{app::{App, AppContext}, testing::Harness, view::View};
+//! use std::io;
+//!
+//! struct List;
+//! struct Detail;
+//!
+//! impl View for List {
+//!     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+//!         "List".render(area, buf);
+//!     }
+//!
+//!     fn handle_events(&mut self, event: &Event, ctx: &AppContext) -> io::Result<()> {
+//!         if let Event::Key(key) = event {
+//!             match key.code {
+//!                 KeyCode::Enter => ctx.push_view(Detail),
+//!                 KeyCode::Char('q') => ctx.quit(),
+//!                 _ => {}
+//!             }
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! impl View for Detail {
+//!     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+//!         "Detail".render(area, buf);
+//!     }
+//!
+//!     fn handle_events(&mut self, event: &Event, ctx: &AppContext) -> io::Result<()> {
+//!         if let Event::Key(key) = event {
+//!             if key.code == KeyCode::Esc {
+//!                 ctx.pop_view();
+//!             }
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let mut harness = Harness::new(App::new(List), 10, 1)?;
+//!
+//! harness.key(KeyCode::Enter).step()?;
+//! assert_eq!(harness.lines(), ["Detail    "]);
+//! assert!(harness.active_view::<Detail>().is_some());
+//!
+//! harness.key(KeyCode::Esc).key(KeyCode::Char('q')).run()?;
+//! assert!(!harness.is_running());
+//! assert_eq!(
+//!     harness.history(),
+//!     [
+//!         std::any::type_name::<List>(),
+//!         std::any::type_name::<Detail>(),
+//!         std::any::type_name::<List>(),
+//!     ]
+//! );
+//! # Ok::<(), ratatuio::Error>(())
+//! ```
+//!
+//! # Snapshots
+//! Rendering of a single view can be compared with a stored text snapshot with [`assert_view_snapshot!`](crate::assert_view_snapshot):
+//!
+//! ```
+//! # use ratatui::{buffer::Buffer, layout::Rect, style::Stylize, widgets::Widget};
+//! # use ratatuio::{testing, view::View};
+//! struct MainPage;
+//!
+//! impl View for MainPage {
+//!     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+//!         "Hello".red().bold().render(area, buf);
+//!     }
+//! }
+//!
+//! let buffer = testing::render_view(&MainPage, 8, 1);
+//! assert_eq!(testing::buffer_to_text(&buffer), "Hello   \n");
+//! assert_eq!(
+//!     testing::buffer_to_styled_text(&buffer),
+//!     "Hello   \n---\n0:0..5 fg=Red mod=BOLD\n"
+//! );
+//! ```
+
+mod snapshot;
+
+#[doc(hidden)]
+pub use snapshot::snapshot_path;
+pub use snapshot::{
+    assert_snapshot, buffer_to_styled_text, buffer_to_text, render_view, UPDATE_SNAPSHOTS_ENV,
+};
+
+use crate::{
+    app::{downcast_exit_value, inbox::Signal, runtime::Schedule, App, AppContext},
+    recording::Recording,
+    Error,
+};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+use std::{any::Any, collections::VecDeque, time::Duration};
+
+/// Runs an [`App`] headlessly against [`TestBackend`] with a scripted queue of [`Event`]s.
+///
+/// Events are queued with [`Harness::event()`], [`Harness::key()`] and similar methods, and processed one at a time
+/// with [`Harness::step()`] or all at once with [`Harness::run()`]. Messages posted with
+/// [`Sender`](crate::app::Sender) are processed on every step. Ticks are not driven by time, but explicitly with
+/// [`Harness::tick()`], so tests are deterministic.
+pub struct Harness {
+    app: App,
+    terminal: Terminal<TestBackend>,
+    schedule: Schedule,
+    events: VecDeque<Event>,
+    started: bool,
+    exit_value: Option<Box<dyn Any + Send>>,
+}
+
+impl Harness {
+    /// Creates new harness running the provided application on a headless terminal of the provided size.
+    ///
+    /// Recording set with [`App::replay()`] is queued like with [`Harness::replay()`].
+    pub fn new(mut app: App, width: u16, height: u16) -> Result<Self, Error> {
+        app.history = Some(Vec::new());
+        app.frame_interval = Duration::ZERO;
+        let replay = app.replay.take();
+
+        let mut harness = Harness {
+            app,
+            terminal: Terminal::new(TestBackend::new(width, height))?,
+            schedule: Schedule::new(),
+            events: VecDeque::new(),
+            started: false,
+            exit_value: None,
+        };
+        if let Some(recording) = replay {
+            harness.replay(&recording);
+        }
+        Ok(harness)
+    }
+
+    /// Returns [`AppContext`] of the tested application.
+    pub fn context(&self) -> AppContext {
+        self.app.context.clone()
+    }
+
+    /// Queues the event.
+    pub fn event(&mut self, event: Event) -> &mut Self {
+        self.events.push_back(event);
+        self
+    }
+
+    /// Queues all provided events.
+    pub fn events(&mut self, events: impl IntoIterator<Item = Event>) -> &mut Self {
+        self.events.extend(events);
+        self
+    }
+
+    /// Queues press of the key without modifiers.
+    pub fn key(&mut self, code: KeyCode) -> &mut Self {
+        self.key_with(code, KeyModifiers::NONE)
+    }
+
+    /// Queues press of the key with modifiers, ex. `Ctrl+C`.
+    pub fn key_with(&mut self, code: KeyCode, modifiers: KeyModifiers) -> &mut Self {
+        self.event(Event::Key(KeyEvent::new(code, modifiers)))
+    }
+
+    /// Queues press of every character of the text.
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        self.events(
+            text.chars()
+                .map(|c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))),
+        )
+    }
+
+    /// Queues resize of the terminal. Size of the headless terminal changes when the event is processed.
+    pub fn resize(&mut self, width: u16, height: u16) -> &mut Self {
+        self.event(Event::Resize(width, height))
+    }
+
+    /// Queues all events of the recording, ex. one attached to a bug report, to turn it into a regression test.
+    ///
+    /// If the recording has initial terminal size, headless terminal is resized to it. Events are processed in the
+    /// recorded order regardless of their timestamps.
+    ///
+    /// ```
+    /// # use crossterm::event::{Event, KeyCode};
+    /// # use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+    /// # use ratatuio::{app::{App, AppContext}, recording::Recording, testing::Harness, view::View};
+    /// # use std::io;
+    /// # struct MainPage;
+    /// # impl View for MainPage {
+    /// #     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+    /// #         format!("{}x{}", area.width, area.height).render(area, buf);
+    /// #     }
+    /// #     fn handle_events(&mut self, event: &Event, ctx: &AppContext) -> io::Result<()> {
+    /// #         if let Event::Key(key) = event {
+    /// #             if key.code == KeyCode::Char('q') {
+    /// #                 ctx.quit();
+    /// #             }
+    /// #         }
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    /// let recording: Recording = "
+    ///     size 20 2
+    ///     0.100000 resize 30 3
+    ///     0.200000 key char:q NONE press NONE
+    /// "
+    /// .parse()?;
+    ///
+    /// let mut harness = Harness::new(App::new(MainPage), 80, 24)?;
+    /// harness.replay(&recording).step()?;
+    /// assert_eq!(harness.buffer().area.width, 30);
+    ///
+    /// harness.run()?;
+    /// assert!(!harness.is_running());
+    /// # Ok::<(), ratatuio::Error>(())
+    /// ```
+    pub fn replay(&mut self, recording: &Recording) -> &mut Self {
+        if let Some((width, height)) = recording.size {
+            self.terminal.backend_mut().resize(width, height);
+        }
+        self.events(
+            recording
+                .events
+                .iter()
+                .map(|recorded| recorded.event.clone()),
+        )
+    }
+
+    /// Processes all posted messages and the next queued event, then draws the current view.
+    ///
+    /// First step also calls [`View::on_enter()`](crate::view::View::on_enter) of the initial view and draws it.
+    ///
+    /// # Returns:
+    /// - `Ok(true)` if anything was processed.
+    /// - `Ok(false)` if there was nothing to process or the application already exited.
+    /// - [`Error`] returned by the view.
+    pub fn step(&mut self) -> Result<bool, Error> {
+        if !self.start()? || !self.is_running() {
+            return Ok(false);
+        }
+
+        let mut signals = self.app.context.inbox().wait(Some(Duration::ZERO));
+        if let Some(event) = self.events.pop_front() {
+            if let Event::Resize(width, height) = event {
+                self.terminal.backend_mut().resize(width, height);
+            }
+            signals.push(Signal::Input(event));
+        }
+
+        if signals.is_empty() {
+            return Ok(false);
+        }
+
+        for signal in signals {
+            if !self.app.prepare_dispatch(&mut self.schedule)? {
+                break;
+            }
+            self.app.dispatch(signal, &mut self.schedule)?;
+        }
+
+        self.finish_step()?;
+        Ok(true)
+    }
+
+    /// Calls [`Harness::step()`] until all queued events and messages are processed or the application exits.
+    pub fn run(&mut self) -> Result<(), Error> {
+        while self.step()? {}
+        Ok(())
+    }
+
+    /// Calls [`View::on_tick()`](crate::view::View::on_tick) of the current view, then draws it.
+    ///
+    /// # Parameters:
+    /// - `elapsed`: Time since the previous tick passed to the view.
+    pub fn tick(&mut self, elapsed: Duration) -> Result<(), Error> {
+        self.start()?;
+        if self.is_running() {
+            self.app.tick_view(elapsed, &mut self.schedule)?;
+            self.finish_step()?;
+        }
+        Ok(())
+    }
+
+    /// Ends the pending count and key sequence of the [`Keymap`](crate::keymap::Keymap) as if its timeout passed, then
+    /// draws the current view. Sequences never time out on their own in the harness, so tests are deterministic.
+    pub fn expire_keys(&mut self) -> Result<(), Error> {
+        self.start()?;
+        if let Some(deadline) = self
+            .app
+            .key_matcher
+            .deadline()
+            .filter(|_| self.is_running())
+        {
+            let events = self.app.expire_keys(deadline, &mut self.schedule)?;
+            self.app.deliver(events)?;
+            self.finish_step()?;
+        }
+        Ok(())
+    }
+
+    /// Hides all toasts shown with [`AppContext::notify()`] as if their timeout passed, then draws the current view.
+    /// Toasts never time out on their own in the harness, so tests are deterministic.
+    pub fn expire_toasts(&mut self) -> Result<(), Error> {
+        self.start()?;
+        if let Some(deadline) = self
+            .app
+            .toasts
+            .last_deadline()
+            .filter(|_| self.is_running())
+        {
+            self.app.expire_toasts(deadline, &mut self.schedule);
+            self.finish_step()?;
+        }
+        Ok(())
+    }
+
+    /// Returns `false` once the application was requested to quit.
+    pub fn is_running(&self) -> bool {
+        self.app.context.is_running()
+    }
+
+    /// Returns buffer of the headless terminal with the last drawn frame.
+    pub fn buffer(&self) -> &Buffer {
+        self.terminal.backend().buffer()
+    }
+
+    /// Returns rows of the last drawn frame as text, without styles.
+    pub fn lines(&self) -> Vec<String> {
+        let buffer = self.buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .filter_map(|x| buffer.cell((x, y)))
+                    .filter(|cell| !cell.skip)
+                    .map(|cell| cell.symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the active view if it is of type `T`.
+    pub fn active_view<T: Any>(&self) -> Option<&T> {
+        let view = self.app.views.last()?.as_any();
+        view.downcast_ref()
+    }
+
+    /// Returns the top overlay opened with [`AppContext::open_overlay()`] if it is of type `T`.
+    pub fn active_overlay<T: Any>(&self) -> Option<&T> {
+        let view = self.app.overlays.last()?.view.as_any();
+        view.downcast_ref()
+    }
+
+    /// Returns type name of the active view, or `None` if the application already exited.
+    pub fn active_view_name(&self) -> Option<&'static str> {
+        self.app.views.last().map(|view| view.type_name())
+    }
+
+    /// Returns type names of views on the navigation stack, from the root view to the active one.
+    pub fn stack(&self) -> Vec<&'static str> {
+        self.app.views.iter().map(|view| view.type_name()).collect()
+    }
+
+    /// Returns type names of views in order they became active, including views resumed after navigating back.
+    pub fn history(&self) -> &[&'static str] {
+        self.app.history.as_deref().unwrap_or_default()
+    }
+
+    /// Takes value provided to [`AppContext::quit_with()`] after the application exited.
+    ///
+    /// # Returns:
+    /// - `Ok(Some(value))` if application exited with [`AppContext::quit_with()`].
+    /// - `Ok(None)` if application is still running, exited with [`AppContext::quit()`] or the value was already taken.
+    /// - [`Error::InvalidExitValue`] if provided value is not of type `T`.
+    pub fn exit_value<T: Any>(&mut self) -> Result<Option<T>, Error> {
+        downcast_exit_value(self.exit_value.take())
+    }
+
+    /// Starts the recording set with [`App::record_to()`], enters the initial view and draws it on the first call.
+    /// Returns `false` if application already exited.
+    fn start(&mut self) -> Result<bool, Error> {
+        if !self.started {
+            self.started = true;
+            self.app.start_recording(&self.terminal)?;
+            self.app.enter()?;
+            self.finish_step()?;
+        }
+        Ok(!self.app.views.is_empty())
+    }
+
+    /// Draws the current view, or exits all views if the application was requested to quit.
+    fn finish_step(&mut self) -> Result<(), Error> {
+        if self.is_running() {
+            self.schedule.invalidate();
+            self.app.draw(&mut self.terminal, &mut self.schedule)
+        } else {
+            self.app.exit()?;
+            self.app.context.inbox().close();
+            self.exit_value = self.app.context.take_exit_value();
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::View;
+    use ratatui::{buffer::Buffer, layout::Rect};
+
+    struct Page;
+
+    impl View for Page {
+        fn render_view(&self, _area: Rect, _buf: &mut Buffer) {}
+    }
+
+    #[test]
+    fn records_events_set_with_record_to() {
+        let path =
+            std::env::temp_dir().join(format!("ratatuio-harness-{}.rec", std::process::id()));
+        let mut harness = Harness::new(App::new(Page).record_to(&path), 20, 4).unwrap();
+        harness.text("hi").resize(30, 5).run().unwrap();
+
+        let recording = Recording::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let events: Vec<Event> = recording
+            .events
+            .into_iter()
+            .map(|recorded| recorded.event)
+            .collect();
+        assert_eq!(recording.size, Some((20, 4)));
+        assert_eq!(
+            events,
+            [
+                Event::Key(KeyCode::Char('h').into()),
+                Event::Key(KeyCode::Char('i').into()),
+                Event::Resize(30, 5),
+            ]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Snapshot testing of [`View`] rendering, see [`assert_view_snapshot!`](crate::assert_view_snapshot).
+
+use crate::view::View;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable which, when set to `1`, makes snapshot assertions overwrite stored snapshots instead of comparing them.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "RATATUIO_UPDATE_SNAPSHOTS";
+
+/// Renders the view with [`View::render_view()`] into a new buffer of the provided size.
+pub fn render_view<V: View + ?Sized>(view: &V, width: u16, height: u16) -> Buffer {
+    let area = Rect::new(0, 0, width, height);
+    let mut buffer = Buffer::empty(area);
+    view.render_view(area, &mut buffer);
+    buffer
+}
+
+/// Returns content of the buffer as text, one line per row, without styles.
+pub fn buffer_to_text(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            if let Some(cell) = buffer.cell((buffer.area.x + x, buffer.area.y + y)) {
+                if !cell.skip {
+                    text.push_str(cell.symbol());
+                }
+            }
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Returns content of the buffer as text followed by annotations of styled cells.
+///
+/// Every run of cells with the same non-default style is annotated with its row, columns and style:
+///
+/// ```text
+/// Hello World
+/// ---
+/// 0:0..5 fg=Red mod=BOLD
+/// ```
+pub fn buffer_to_styled_text(buffer: &Buffer) -> String {
+    let mut text = buffer_to_text(buffer);
+    text.push_str("---\n");
+
+    for y in 0..buffer.area.height {
+        let mut run: Option<(u16, Style)> = None;
+        for x in 0..=buffer.area.width {
+            let style = buffer
+                .cell((buffer.area.x + x, buffer.area.y + y))
+                .filter(|_| x < buffer.area.width)
+                .map(|cell| cell.style());
+
+            match (run, style) {
+                (Some((_, current)), Some(style)) if current == style => continue,
+                (Some((start, current)), _) => {
+                    annotate(&mut text, y, start, x, current);
+                    run = None;
+                }
+                (None, _) => {}
+            }
+
+            if let Some(style) = style.filter(|style| !is_plain(*style)) {
+                run = Some((x, style));
+            }
+        }
+    }
+
+    text
+}
+
+fn is_plain(style: Style) -> bool {
+    let plain = |color: Option<Color>| color.unwrap_or(Color::Reset) == Color::Reset;
+    plain(style.fg) && plain(style.bg) && style.add_modifier.is_empty()
+}
+
+fn annotate(text: &mut String, y: u16, start: u16, end: u16, style: Style) {
+    let _ = write!(text, "{y}:{start}..{end}");
+    if let Some(fg) = style.fg.filter(|fg| *fg != Color::Reset) {
+        let _ = write!(text, " fg={fg}");
+    }
+    if let Some(bg) = style.bg.filter(|bg| *bg != Color::Reset) {
+        let _ = write!(text, " bg={bg}");
+    }
+    if !style.add_modifier.is_empty() {
+        let _ = write!(text, " mod={:?}", style.add_modifier);
+    }
+    text.push('\n');
+}
+
+/// Compares the text with snapshot stored in the file and panics if they differ.
+///
+/// If the environment variable [`UPDATE_SNAPSHOTS_ENV`] is set to `1`, the snapshot is written instead.
+///
+/// # Panics:
+/// - If the snapshot does not exist or differs from the text.
+/// - If the snapshot could not be written.
+pub fn assert_snapshot(path: impl AsRef<Path>, actual: &str) {
+    let path = path.as_ref();
+
+    if env::var(UPDATE_SNAPSHOTS_ENV).is_ok_and(|value| value == "1") {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("failed to create snapshot directory");
+        }
+        fs::write(path, actual).expect("failed to write snapshot");
+        return;
+    }
+
+    match fs::read_to_string(path) {
+        Ok(expected) if expected == actual => {}
+        Ok(expected) => panic!(
+            "snapshot {} does not match\n\nexpected:\n{expected}\nactual:\n{actual}\nRun with {UPDATE_SNAPSHOTS_ENV}=1 to update it.",
+            path.display()
+        ),
+        Err(_) => panic!(
+            "snapshot {} does not exist\n\nactual:\n{actual}\nRun with {UPDATE_SNAPSHOTS_ENV}=1 to create it.",
+            path.display()
+        ),
+    }
+}
+
+thread_local! {
+    /// Number of snapshots already asserted by each test function on this thread.
+    static SNAPSHOT_COUNTS: RefCell<HashMap<String, usize>> = RefCell::new(HashMap::new());
+}
+
+/// Returns path of the snapshot named after the test function, used by [`assert_view_snapshot!`](crate::assert_view_snapshot).
+///
+/// Snapshots are stored in `snapshots` directory of the crate, unless the name is an absolute path. Second and later
+/// snapshots asserted by the same function get a numbered suffix.
+#[doc(hidden)]
+pub fn snapshot_path(manifest_dir: &str, function: &str, name: Option<&Path>) -> PathBuf {
+    let function = function.strip_suffix("::__f").unwrap_or(function);
+    let name = match name {
+        Some(name) => name.as_os_str().to_owned(),
+        None => {
+            let count = SNAPSHOT_COUNTS.with_borrow_mut(|counts| {
+                let count = counts.entry(function.to_owned()).or_default();
+                *count += 1;
+                *count
+            });
+            let name = function.replace("::", "__");
+            match count {
+                1 => name.into(),
+                count => format!("{name}-{count}").into(),
+            }
+        }
+    };
+
+    let mut file = name;
+    file.push(".snap");
+    Path::new(manifest_dir).join("snapshots").join(file)
+}
+
+/// Renders the [`View`](crate::view::View) into a buffer of the provided size and compares it with the snapshot stored
+/// in `snapshots` directory of the crate.
+///
+/// Snapshot is named after the test function, unless the name is provided as the first argument. Names are relative
+/// to the `snapshots` directory, absolute paths are used as they are. Passing `styled` as the last argument also
+/// compares styles of the cells, see [`buffer_to_styled_text()`](crate::testing::buffer_to_styled_text).
+/// Set environment variable `RATATUIO_UPDATE_SNAPSHOTS=1` to create or update snapshots.
+///
+/// ```no_run
+/// # use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+/// # use ratatuio::{assert_view_snapshot, view::View};
+/// struct MainPage;
+///
+/// impl View for MainPage {
+///     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+///         "Hello World!".render(area, buf);
+///     }
+/// }
+///
+/// // snapshots/<module>__<test>.snap
+/// assert_view_snapshot!(MainPage, 20, 1);
+/// // snapshots/main_page_styled.snap
+/// assert_view_snapshot!("main_page_styled", MainPage, 20, 1, styled);
+/// ```
+///
+/// A snapshot which does not exist or differs from the rendered view fails the test with both versions printed:
+///
+/// ```
+/// # use ratatui::{buffer::Buffer, layout::Rect, style::Stylize, widgets::Widget};
+/// # use ratatuio::{assert_view_snapshot, testing::UPDATE_SNAPSHOTS_ENV, view::View};
+/// # use std::{env, fs, panic};
+/// struct Greeting(&'static str);
+///
+/// impl View for Greeting {
+///     fn render_view(&self, area: Rect, buf: &mut Buffer) {
+///         self.0.green().render(area, buf);
+///     }
+/// }
+///
+/// let dir = env::temp_dir().join(format!("ratatuio-snapshots-{}", std::process::id()));
+/// let (text, styled) = (dir.join("greeting"), dir.join("greeting_styled"));
+///
+/// let missing = panic::catch_unwind(|| assert_view_snapshot!(&text, Greeting("Hi"), 4, 1));
+/// assert!(missing.is_err());
+///
+/// env::set_var(UPDATE_SNAPSHOTS_ENV, "1");
+/// assert_view_snapshot!(&text, Greeting("Hi"), 4, 1);
+/// assert_view_snapshot!(&styled, Greeting("Hi"), 4, 1, styled);
+/// env::remove_var(UPDATE_SNAPSHOTS_ENV);
+/// assert_eq!(fs::read_to_string(dir.join("greeting.snap"))?, "Hi  \n");
+/// assert_eq!(fs::read_to_string(dir.join("greeting_styled.snap"))?, "Hi  \n---\n0:0..2 fg=Green\n");
+///
+/// assert_view_snapshot!(&text, Greeting("Hi"), 4, 1);
+/// assert_view_snapshot!(&styled, Greeting("Hi"), 4, 1, styled);
+/// let changed = panic::catch_unwind(|| assert_view_snapshot!(&text, Greeting("Bye"), 4, 1));
+/// assert!(changed.is_err());
+/// # fs::remove_dir_all(dir)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[macro_export]
+macro_rules! assert_view_snapshot {
+    ($view:expr, $width:expr, $height:expr, styled $(,)?) => {
+        $crate::__assert_view_snapshot!(None, $view, $width, $height, buffer_to_styled_text)
+    };
+    ($name:expr, $view:expr, $width:expr, $height:expr, styled $(,)?) => {
+        $crate::__assert_view_snapshot!(
+            Some(::std::path::Path::new(&$name)),
+            $view,
+            $width,
+            $height,
+            buffer_to_styled_text
+        )
+    };
+    ($name:expr, $view:expr, $width:expr, $height:expr $(,)?) => {
+        $crate::__assert_view_snapshot!(
+            Some(::std::path::Path::new(&$name)),
+            $view,
+            $width,
+            $height,
+            buffer_to_text
+        )
+    };
+    ($view:expr, $width:expr, $height:expr $(,)?) => {
+        $crate::__assert_view_snapshot!(None, $view, $width, $height, buffer_to_text)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_view_snapshot {
+    ($name:expr, $view:expr, $width:expr, $height:expr, $format:ident) => {{
+        fn __f() {}
+        fn __type_name_of<T>(_: T) -> &'static str {
+            ::std::any::type_name::<T>()
+        }
+
+        let path =
+            $crate::testing::snapshot_path(env!("CARGO_MANIFEST_DIR"), __type_name_of(__f), $name);
+        let buffer = $crate::testing::render_view(&$view, $width, $height);
+        $crate::testing::assert_snapshot(path, &$crate::testing::$format(&buffer));
+    }};
+}
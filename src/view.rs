@@ -1,24 +1,285 @@
 //! See [`View`].
 
+use crate::{
+    app::{AppContext, Message},
+    keymap::Action,
+};
 use crossterm::event::Event;
 use ratatui::{buffer::Buffer, layout::Rect, widgets::WidgetRef};
-use std::io;
+use std::{any::Any, io, time::Duration};
+
+/// Future returned by [`View::handle_events_async()`].
+#[cfg(feature = "tokio")]
+pub type ViewFuture<'a> = std::pin::Pin<Box<dyn std::future::Future<Output = io::Result<()>> + 'a>>;
 
 /// Trait representing a view of application.
 ///
 /// This trait can be used to define the rendering [`View::render_view()`] of its properties and how it should handle events [`View::handle_events()`].
+///
+/// Events are passed together with [`AppContext`] of the running application, which can be used to quit or to navigate to other views.
+///
+/// # Lifecycle
+/// Application loop notifies the view when its state in the navigation stack changes:
+/// - [`View::on_enter()`] - view became active, after [`App::new()`](crate::app::App::new), `push_view()` or `replace_view()`.
+/// - [`View::on_suspend()`] - view was covered by another view pushed with `push_view()`.
+/// - [`View::on_resume()`] - view became active again after views covering it were popped.
+/// - [`View::on_exit()`] - view was popped, replaced or the application is exiting. View is dropped afterwards.
+///
+/// ```
+/// # use ratatui::{buffer::Buffer, layout::Rect};
+/// # use ratatuio::{app::{App, AppContext}, testing::Harness, view::View};
+/// # use std::{io, sync::{Arc, Mutex}};
+/// struct Page {
+///     name: &'static str,
+///     log: Arc<Mutex<Vec<String>>>,
+/// }
+///
+/// impl View for Page {
+/// #   fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+///     fn on_suspend(&mut self, _ctx: &AppContext) -> io::Result<()> {
+///         self.log.lock().unwrap().push(format!("{} suspend", self.name));
+///         Ok(())
+///     }
+///
+///     fn on_resume(&mut self, _ctx: &AppContext) -> io::Result<()> {
+///         self.log.lock().unwrap().push(format!("{} resume", self.name));
+///         Ok(())
+///     }
+/// }
+///
+/// let log = Arc::new(Mutex::new(Vec::new()));
+/// let page = |name| Page { name, log: log.clone() };
+/// let mut harness = Harness::new(App::new(page("list")), 10, 1)?;
+///
+/// harness.context().push_view(page("details"));
+/// harness.context().pop_view();
+/// harness.run()?;
+/// assert_eq!(*log.lock().unwrap(), ["list suspend", "list resume"]);
+/// # Ok::<(), ratatuio::Error>(())
+/// ```
 pub trait View {
-    fn handle_events(&mut self, _event: &Event) -> io::Result<()> {
+    /// Called when the view becomes active for the first time, ex. to start loading data.
+    fn on_enter(&mut self, _ctx: &AppContext) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called when the view is removed from the navigation stack or the application is exiting, ex. to release resources.
+    fn on_exit(&mut self, _ctx: &AppContext) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called when the view is covered by another view. Suspended view keeps its state, but receives no events.
+    fn on_suspend(&mut self, _ctx: &AppContext) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called when the view becomes active again after being suspended.
+    fn on_resume(&mut self, _ctx: &AppContext) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn handle_events(&mut self, _event: &Event, _ctx: &AppContext) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Async version of [`View::handle_events()`] used by [`App::run_async()`](crate::app::App::run_async).
+    /// By default calls [`View::handle_events()`].
+    ///
+    /// ```
+    /// # use crossterm::event::Event;
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// # use ratatuio::{app::AppContext, view::{View, ViewFuture}};
+    /// struct MainPage;
+    ///
+    /// impl View for MainPage {
+    /// #   fn render_view(&self, area: Rect, buf: &mut Buffer) {}
+    ///     fn handle_events_async<'a>(&'a mut self, event: &'a Event, ctx: &'a AppContext) -> ViewFuture<'a> {
+    ///         Box::pin(async move {
+    ///             tokio::task::yield_now().await;
+    ///             Ok(())
+    ///         })
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "tokio")]
+    fn handle_events_async<'a>(
+        &'a mut self,
+        event: &'a Event,
+        ctx: &'a AppContext,
+    ) -> ViewFuture<'a> {
+        Box::pin(async move { self.handle_events(event, ctx) })
+    }
+
+    /// Returns name of the [`Keymap`](crate::keymap::Keymap) context whose bindings apply while the view is active,
+    /// in addition to the global ones. Defaults to `None`, so only global bindings apply.
+    fn keymap_context(&self) -> Option<&str> {
+        None
+    }
+
+    /// Called instead of [`View::handle_events()`] when the pressed key is bound to an action in the keymap set with
+    /// [`App::keymap()`](crate::app::App::keymap). See [`keymap`](crate::keymap).
+    fn handle_action(&mut self, _action: &Action, _ctx: &AppContext) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called after mode of the view was changed with [`AppContext::set_mode()`], ex. to change the cursor shape.
+    ///
+    /// # Parameters:
+    /// - `previous`: Mode before the change, `None` if it was not set yet.
+    /// - `mode`: The new mode.
+    fn on_mode_change(
+        &mut self,
+        _previous: Option<&str>,
+        _mode: &str,
+        _ctx: &AppContext,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called for every [`Message`] posted with [`Sender::send()`](crate::app::Sender::send) while the view is active.
+    fn handle_message(&mut self, _message: Message, _ctx: &AppContext) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called periodically while the view is active, if application was started with [`App::tick_rate()`](crate::app::App::tick_rate).
+    ///
+    /// # Parameters:
+    /// - `elapsed`: Time since the previous tick.
+    fn on_tick(&mut self, _elapsed: Duration, _ctx: &AppContext) -> io::Result<()> {
         Ok(())
     }
 
     fn render_view(&self, area: Rect, buf: &mut Buffer);
 }
 
-pub(crate) struct ViewWidgetWrapper<'a>(pub(crate) &'a Box<dyn View + Send + Sync>);
+/// A [`View`] which can be downcast to its concrete type, used internally to store views in the navigation stack.
+///
+/// Conversions to [`View`] and [`Any`] are explicit methods, because trait upcasting coercion requires Rust 1.86.
+pub(crate) trait AnyView: View + Any + Send + Sync {
+    /// Returns type name of the view, ex. `"my_app::views::MainPage"`.
+    fn type_name(&self) -> &'static str;
+
+    fn as_view(&self) -> &(dyn View + Send + Sync);
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn into_view(self: Box<Self>) -> Box<dyn View + Send + Sync>;
+}
+
+impl<T: View + Any + Send + Sync> AnyView for T {
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn as_view(&self) -> &(dyn View + Send + Sync) {
+        self
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_view(self: Box<Self>) -> Box<dyn View + Send + Sync> {
+        self
+    }
+}
+
+pub(crate) type BoxedView = Box<dyn AnyView + Send + Sync>;
+
+pub(crate) struct ViewWidgetWrapper<'a>(pub(crate) &'a (dyn View + Send + Sync));
 
 impl<'a> WidgetRef for ViewWidgetWrapper<'a> {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
         self.0.render_view(area, buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app::App, testing::Harness};
+    use crossterm::event::KeyCode;
+    use std::sync::{Arc, Mutex};
+
+    struct Page {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Page {
+        fn log(&self, hook: &str) -> io::Result<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{} {hook}", self.name));
+            Ok(())
+        }
+    }
+
+    impl View for Page {
+        fn render_view(&self, _area: Rect, _buf: &mut Buffer) {}
+
+        fn on_enter(&mut self, _ctx: &AppContext) -> io::Result<()> {
+            self.log("enter")
+        }
+
+        fn on_exit(&mut self, _ctx: &AppContext) -> io::Result<()> {
+            self.log("exit")
+        }
+
+        fn on_suspend(&mut self, _ctx: &AppContext) -> io::Result<()> {
+            self.log("suspend")
+        }
+
+        fn on_resume(&mut self, _ctx: &AppContext) -> io::Result<()> {
+            self.log("resume")
+        }
+
+        fn handle_events(&mut self, _event: &Event, ctx: &AppContext) -> io::Result<()> {
+            ctx.quit();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn lifecycle_hooks_follow_navigation_and_quit() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let page = |name| Page {
+            name,
+            log: log.clone(),
+        };
+        let mut harness = Harness::new(App::new(page("a")), 10, 1).unwrap();
+        let ctx = harness.context();
+
+        ctx.push_view(page("b"));
+        ctx.push_view(page("c"));
+        ctx.replace_view(page("d"));
+        ctx.pop_view();
+        harness.run().unwrap();
+        ctx.push_view(page("e"));
+        ctx.pop_to_root();
+        harness.run().unwrap();
+        harness.key(KeyCode::Char('q')).run().unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "a enter",
+                "a suspend",
+                "b enter",
+                "b suspend",
+                "c enter",
+                "c exit",
+                "d enter",
+                "d exit",
+                "b resume",
+                "b suspend",
+                "e enter",
+                "e exit",
+                "b exit",
+                "a resume",
+                "a exit",
+            ]
+        );
+    }
+}